@@ -7,8 +7,18 @@ pub struct Program {
 #[derive(Debug, Clone)]
 pub enum Stmt {
     Fn(Box<StmtFn>),
+    Record(Box<StmtRecord>),
+    Enum(Box<StmtEnum>),
     Let(Box<StmtLet>),
+    Assign(Box<StmtAssign>),
+    While(Box<StmtWhile>),
+    For(Box<StmtFor>),
+    Return(Box<StmtReturn>),
+    Break(Box<StmtBreak>),
+    Continue(Box<StmtContinue>),
     Expr(Box<Expr>),
+    /// a statement that failed to parse, kept so the parser can go on
+    Error(Box<StmtError>),
 }
 
 #[derive(Debug, Clone)]
@@ -16,11 +26,87 @@ pub struct StmtFn {
     pub name: String,
     pub params: Vec<String>,
     pub body: Block,
+    pub span: crate::span::Span,
 }
+/// `bentuk Name { field, so about method() { ... } }`
+#[derive(Debug, Clone)]
+pub struct StmtRecord {
+    pub name: String,
+    pub fields: Vec<String>,
+    pub methods: Vec<StmtFn>,
+    pub span: crate::span::Span,
+}
+
+/// `jenis Name { Variant, Variant(field, ...) }`
+#[derive(Debug, Clone)]
+pub struct StmtEnum {
+    pub name: String,
+    pub variants: Vec<EnumVariant>,
+    pub span: crate::span::Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct EnumVariant {
+    pub name: String,
+    pub fields: Vec<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct StmtLet {
     pub name: String,
     pub value: Expr,
+    /// declared with `seriously`, can't be reassigned
+    pub constant: bool,
+    pub span: crate::span::Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct StmtAssign {
+    pub target: Expr,
+    pub value: Expr,
+    pub span: crate::span::Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct StmtWhile {
+    pub cond: Expr,
+    pub body: Block,
+    /// the span of `cond`
+    pub span: crate::span::Span,
+}
+
+/// `tiap name dari iter { body }`, or over the integers from `iter` up to
+/// (excluding) `end` with `tiap name dari iter sampe end { body }`
+#[derive(Debug, Clone)]
+pub struct StmtFor {
+    pub name: String,
+    pub iter: Expr,
+    pub end: Option<Expr>,
+    pub body: Block,
+    /// from the start of `iter` to the end of `end`
+    pub span: crate::span::Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct StmtReturn {
+    pub value: Option<Expr>,
+    pub span: crate::span::Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct StmtBreak {
+    pub span: crate::span::Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct StmtContinue {
+    pub span: crate::span::Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct StmtError {
+    /// the tokens skipped while recovering
+    pub span: crate::span::Span,
 }
 
 #[derive(Debug, Clone)]
@@ -28,23 +114,40 @@ pub enum Expr {
     If(Box<ExprIf>),
     Block(Box<Block>),
     Str(Box<ExprStr>),
+    Template(Box<ExprTemplate>),
     Int(Box<ExprInt>),
+    /// an int literal outside of the `i64` range
+    BigInt(Box<ExprBigInt>),
+    Float(Box<ExprFloat>),
+    Bool(Box<ExprBool>),
+    /// `kosong`
+    None(Box<ExprNone>),
     Identifier(Box<ExprIdent>),
     Call(Box<ExprCall>),
     Binary(Box<ExprBinary>),
     Unary(Box<ExprUnary>),
+    Lambda(Box<ExprLambda>),
+    List(Box<ExprList>),
+    Map(Box<ExprMap>),
+    Index(Box<ExprIndex>),
+    Slice(Box<ExprSlice>),
+    Field(Box<ExprField>),
+    Match(Box<ExprMatch>),
 }
 
 #[derive(Debug, Clone)]
 pub struct ExprIf {
     pub branches: Vec<IfBranch>,
     pub tail: Option<Block>,
+    pub span: crate::span::Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct IfBranch {
     pub cond: Expr,
     pub body: Block,
+    /// the span of `cond`
+    pub span: crate::span::Span,
 }
 
 #[derive(Debug, Clone)]
@@ -61,6 +164,13 @@ pub enum BinaryOp {
     Subtract,
     Multiply,
     Divide,
+    Modulo,
+    Power,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
     Or,
     And,
     Equal,
@@ -71,45 +181,216 @@ pub enum BinaryOp {
     GreaterOrEqual,
 }
 
+// the spelling used in the source, for messages
+impl std::fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            BinaryOp::Add => "tambah",
+            BinaryOp::Subtract => "kurang",
+            BinaryOp::Multiply => "kali",
+            BinaryOp::Divide => "bagi",
+            BinaryOp::Modulo => "sisa",
+            BinaryOp::Power => "pangkat",
+            BinaryOp::BitAnd => "bit dan",
+            BinaryOp::BitOr => "bit atau",
+            BinaryOp::BitXor => "bit xor",
+            BinaryOp::ShiftLeft => "geser kiri",
+            BinaryOp::ShiftRight => "geser kanan",
+            BinaryOp::Or => "atau",
+            BinaryOp::And => "dan",
+            BinaryOp::Equal => "sama dengan",
+            BinaryOp::NotEqual => "gak",
+            BinaryOp::LessThan => "lebih kecil",
+            BinaryOp::LessOrEqual => "lebih kecil sama dengan",
+            BinaryOp::GreaterThan => "lebih gede",
+            BinaryOp::GreaterOrEqual => "lebih gede sama dengan",
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ExprUnary {
     pub rhs: Expr,
     pub op: UnaryOp,
+    /// the span of the operator
+    pub span: crate::span::Span,
 }
 
 #[derive(Debug, Clone)]
 pub enum UnaryOp {
     Minus,
     Not,
+    BitNot,
 }
 
 #[derive(Debug, Clone)]
 pub struct ExprCall {
     pub callee: Expr,
     pub args: Vec<Expr>,
+    /// from the start of `callee` to `)`
+    pub span: crate::span::Span,
+}
+
+/// an anonymous function, `so about (params) { body }`
+#[derive(Debug, Clone)]
+pub struct ExprLambda {
+    pub params: Vec<String>,
+    pub body: Block,
+    pub span: crate::span::Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExprList {
+    pub items: Vec<Expr>,
+    pub span: crate::span::Span,
+}
+
+/// `[key: value, ...]`, or `[:]` when empty
+#[derive(Debug, Clone)]
+pub struct ExprMap {
+    pub entries: Vec<MapEntry>,
+    pub span: crate::span::Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct MapEntry {
+    pub key: Expr,
+    pub value: Expr,
+    /// the span of `key`
+    pub span: crate::span::Span,
+}
+
+/// `target[index]`
+#[derive(Debug, Clone)]
+pub struct ExprIndex {
+    pub target: Expr,
+    pub index: Expr,
+    /// the span of `index`
+    pub span: crate::span::Span,
+}
+
+/// `target[start:end]`, either bound can be left out
+#[derive(Debug, Clone)]
+pub struct ExprSlice {
+    pub target: Expr,
+    pub start: Option<Expr>,
+    pub end: Option<Expr>,
+    /// from `[` to `]`
+    pub span: crate::span::Span,
+}
+
+/// `target.name`
+#[derive(Debug, Clone)]
+pub struct ExprField {
+    pub target: Expr,
+    pub name: String,
+    /// the span of `name`
+    pub span: crate::span::Span,
+}
+
+/// `cocokin value { pattern => body, pattern kalo guard => body }`
+#[derive(Debug, Clone)]
+pub struct ExprMatch {
+    pub value: Expr,
+    pub arms: Vec<MatchArm>,
+    /// the span of `value`
+    pub span: crate::span::Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expr>,
+    pub body: Expr,
+    /// the span of `pattern`
+    pub span: crate::span::Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    /// `_`, matches anything
+    Wildcard,
+    /// a lowercase name, matches anything and binds it
+    Binding(String),
+    /// a literal like `1`, `"a"` or `yoi`, matches equal values
+    Literal(Expr),
+    Variant(PatternVariant),
+}
+
+/// `Name` or `Name(pattern, ...)`, names starting with an uppercase letter
+/// are variants
+#[derive(Debug, Clone)]
+pub struct PatternVariant {
+    pub name: String,
+    pub fields: Option<Vec<Pattern>>,
+    pub span: crate::span::Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct ExprInt {
     pub value: i64,
+    pub span: crate::span::Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExprBigInt {
+    pub value: crate::bigint::BigInt,
+    pub span: crate::span::Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExprFloat {
+    pub value: f64,
+    pub span: crate::span::Span,
+}
+
+/// `yoi` or `nope`
+#[derive(Debug, Clone)]
+pub struct ExprBool {
+    pub value: bool,
+    pub span: crate::span::Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExprNone {
+    pub span: crate::span::Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct ExprStr {
     pub value: String,
+    pub span: crate::span::Span,
+}
+
+/// a string literal with `{expr}` placeholders
+#[derive(Debug, Clone)]
+pub struct ExprTemplate {
+    pub parts: Vec<TemplatePart>,
+    pub span: crate::span::Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum TemplatePart {
+    Str(String),
+    Expr(Expr),
 }
 
 #[derive(Debug, Clone)]
 pub struct ExprIdent {
     pub name: String,
+    pub span: crate::span::Span,
 }
 
 #[derive(Debug)]
 pub struct ExprBlock {
     pub inner: Block,
 }
+
+/// `{ body tail }`
 #[derive(Debug, Clone)]
 pub struct Block {
     pub body: Vec<Stmt>,
     pub tail: Option<Expr>,
+    /// from `{` to `}`
+    pub span: crate::span::Span,
 }
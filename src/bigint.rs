@@ -0,0 +1,329 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// each limb holds 9 decimal digits, which keeps printing simple
+const BASE: u64 = 1_000_000_000;
+
+/// an arbitrary-precision integer, used once an int overflows when the
+/// evaluator runs with big ints enabled
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BigInt {
+    negative: bool,
+    /// the magnitude, least significant limb first, without trailing zero
+    /// limbs, so zero has no limbs
+    limbs: Vec<u32>,
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let mut magnitude = value.unsigned_abs();
+        let mut limbs = Vec::new();
+        while magnitude > 0 {
+            limbs.push((magnitude % BASE) as u32);
+            magnitude /= BASE;
+        }
+        BigInt {
+            negative: value < 0,
+            limbs,
+        }
+    }
+}
+
+impl BigInt {
+    fn new(negative: bool, mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigInt {
+            negative: negative && !limbs.is_empty(),
+            limbs,
+        }
+    }
+
+    /// the value of a run of decimal digits like an int literal, `None` if
+    /// `digits` is empty or holds anything else
+    pub fn from_digits(digits: &str) -> Option<BigInt> {
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let limbs = digits
+            .as_bytes()
+            .rchunks(9)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .fold(0, |limb, digit| limb * 10 + (digit - b'0') as u32)
+            })
+            .collect();
+        Some(BigInt::new(false, limbs))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// the value as an `i64`, if it fits
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.limbs.len() > 3 {
+            return None;
+        }
+        let magnitude = self
+            .limbs
+            .iter()
+            .rev()
+            .fold(0i128, |acc, &limb| acc * BASE as i128 + limb as i128);
+        let value = if self.negative { -magnitude } else { magnitude };
+        i64::try_from(value).ok()
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self
+            .limbs
+            .iter()
+            .rev()
+            .fold(0.0, |acc, &limb| acc * BASE as f64 + limb as f64);
+        if self.negative { -magnitude } else { magnitude }
+    }
+
+    /// about how many decimal digits `self` raised to `exp` has, without
+    /// computing it
+    pub fn pow_digits(&self, exp: f64) -> f64 {
+        let Some(&top) = self.limbs.last() else {
+            return 1.0;
+        };
+        let log10 = 9.0 * (self.limbs.len() - 1) as f64 + (top as f64).log10();
+        (log10 * exp).floor() + 1.0
+    }
+
+    pub fn neg(&self) -> BigInt {
+        BigInt::new(!self.negative, self.limbs.clone())
+    }
+
+    pub fn add(&self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add_magnitude(&self.limbs, &other.limbs));
+        }
+        match cmp_magnitude(&self.limbs, &other.limbs) {
+            Ordering::Less => BigInt::new(other.negative, sub_magnitude(&other.limbs, &self.limbs)),
+            _ => BigInt::new(self.negative, sub_magnitude(&self.limbs, &other.limbs)),
+        }
+    }
+
+    pub fn sub(&self, other: &BigInt) -> BigInt {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &BigInt) -> BigInt {
+        let mut limbs = vec![0u64; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let current = limbs[i + j] + a as u64 * b as u64 + carry;
+                limbs[i + j] = current % BASE;
+                carry = current / BASE;
+            }
+            limbs[i + other.limbs.len()] += carry;
+        }
+        let limbs = limbs.into_iter().map(|limb| limb as u32).collect();
+        BigInt::new(self.negative != other.negative, limbs)
+    }
+
+    pub fn pow(&self, mut exp: u32) -> BigInt {
+        let mut result = BigInt::from(1);
+        let mut base = self.clone();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.mul(&base);
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.mul(&base);
+            }
+        }
+        result
+    }
+
+    /// the quotient rounded toward zero and the remainder, which has the
+    /// sign of `self` like `i64` division, `None` when dividing by zero
+    pub fn div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None;
+        }
+        let divisor = BigInt::new(false, other.limbs.clone());
+        let mut quotient = vec![0u32; self.limbs.len()];
+        let mut remainder = BigInt::from(0);
+        for i in (0..self.limbs.len()).rev() {
+            let mut limbs = vec![self.limbs[i]];
+            limbs.extend_from_slice(&remainder.limbs);
+            remainder = BigInt::new(false, limbs);
+
+            // the largest digit with `divisor * digit <= remainder`
+            let (mut low, mut high) = (0, BASE - 1);
+            while low < high {
+                let mid = (low + high).div_ceil(2);
+                let product = divisor.mul(&BigInt::from(mid as i64));
+                if cmp_magnitude(&product.limbs, &remainder.limbs) == Ordering::Greater {
+                    high = mid - 1;
+                } else {
+                    low = mid;
+                }
+            }
+            quotient[i] = low as u32;
+            remainder = remainder.sub(&divisor.mul(&BigInt::from(low as i64)));
+        }
+        Some((
+            BigInt::new(self.negative != other.negative, quotient),
+            BigInt::new(self.negative, remainder.limbs),
+        ))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.limbs, &other.limbs),
+            (true, true) => cmp_magnitude(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some((last, rest)) = self.limbs.split_last() else {
+            return write!(f, "0");
+        };
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{last}")?;
+        for limb in rest.iter().rev() {
+            write!(f, "{limb:09}")?;
+        }
+        Ok(())
+    }
+}
+
+fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut limbs = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0;
+    for i in 0..a.len().max(b.len()) {
+        let sum = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        limbs.push((sum % BASE) as u32);
+        carry = sum / BASE;
+    }
+    if carry > 0 {
+        limbs.push(carry as u32);
+    }
+    limbs
+}
+
+/// `a - b`, where `a` is at least `b`
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut limbs = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+    for (i, &limb) in a.iter().enumerate() {
+        let mut diff = limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if diff < 0 {
+            diff += BASE as i64;
+            borrow = 1;
+        }
+        limbs.push(diff as u32);
+    }
+    limbs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(value: i64) -> BigInt {
+        BigInt::from(value)
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let max = big(i64::MAX);
+        let sum = max.add(&big(1));
+        assert_eq!(sum.to_string(), "9223372036854775808");
+        assert_eq!(sum.to_i64(), None);
+        assert_eq!(sum.sub(&big(1)).to_i64(), Some(i64::MAX));
+        assert_eq!(big(i64::MIN).to_string(), "-9223372036854775808");
+        assert_eq!(big(i64::MIN).to_i64(), Some(i64::MIN));
+        assert_eq!(big(-5).add(&big(3)).to_string(), "-2");
+        assert_eq!(big(5).sub(&big(5)), big(0));
+
+        let square = max.mul(&max);
+        assert_eq!(square.to_string(), "85070591730234615847396907784232501249");
+        assert_eq!(
+            square.mul(&big(-1)).to_string(),
+            "-85070591730234615847396907784232501249"
+        );
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let max = big(i64::MAX);
+        let square = max.mul(&max);
+        let (q, r) = square.add(&big(7)).div_rem(&max).unwrap();
+        assert_eq!((q, r), (max.clone(), big(7)));
+
+        // rounds toward zero like `i64`
+        let (q, r) = big(-7).div_rem(&big(2)).unwrap();
+        assert_eq!((q, r), (big(-3), big(-1)));
+        let (q, r) = big(7).div_rem(&big(-2)).unwrap();
+        assert_eq!((q, r), (big(-3), big(1)));
+
+        assert_eq!(big(1).div_rem(&big(0)), None);
+    }
+
+    #[test]
+    fn test_pow() {
+        assert_eq!(big(2).pow(64).to_string(), "18446744073709551616");
+        assert_eq!(big(-3).pow(3), big(-27));
+        assert_eq!(big(7).pow(0), big(1));
+    }
+
+    #[test]
+    fn test_from_digits() {
+        let digits = "123456789012345678901234567890";
+        assert_eq!(BigInt::from_digits(digits).unwrap().to_string(), digits);
+        assert_eq!(BigInt::from_digits("000042"), Some(big(42)));
+        assert_eq!(BigInt::from_digits("0"), Some(big(0)));
+        assert_eq!(BigInt::from_digits(""), None);
+        assert_eq!(BigInt::from_digits("-1"), None);
+    }
+
+    #[test]
+    fn test_pow_digits() {
+        assert_eq!(big(2).pow_digits(64.0), 20.0);
+        assert_eq!(big(10).pow_digits(9.0), 10.0);
+        assert_eq!(big(-1).pow_digits(1e12), 1.0);
+        assert_eq!(big(0).pow_digits(1e12), 1.0);
+        let huge = big(i64::MAX).mul(&big(i64::MAX));
+        assert_eq!(huge.pow_digits(2.0), huge.pow(2).to_string().len() as f64);
+    }
+
+    #[test]
+    fn test_ordering() {
+        let huge = big(i64::MAX).mul(&big(10));
+        assert!(huge > big(i64::MAX));
+        assert!(huge.neg() < big(i64::MIN));
+        assert!(big(-1) < big(0));
+        assert_eq!(huge.to_f64(), i64::MAX as f64 * 10.0);
+    }
+}
@@ -0,0 +1,167 @@
+mod repl;
+
+use std::io::{self, IsTerminal, Write};
+use std::process::ExitCode;
+
+use jaksel_lang::{
+    ast::Program,
+    diagnostics::Renderer,
+    error::{Error, ErrorKind},
+    eval::Evaluator,
+    lexer::lex,
+    parse_all,
+};
+
+const USAGE: &str = "\
+usage: jaksel <command> [args]
+
+commands:
+    run <file>       run a script
+    repl             start an interactive session
+    tokens <file>    print the token stream of a script
+    ast <file>       print the syntax tree of a script
+    explain [code]   describe an error code like JKS0005, or list them all
+    help             print this message
+
+options:
+    --big-ints       let ints grow past 64 bits instead of failing on overflow
+
+`jaksel <file>` is a shorthand for `jaksel run <file>`.";
+
+const COMMANDS: &[&str] = &["run", "repl", "tokens", "ast", "parse", "explain", "help"];
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let big_ints = take_flag(&mut args, "--big-ints");
+    let run = |path: &str, code: &str| run(path, code, big_ints);
+
+    match args.as_slice() {
+        ["run", path] => with_source(path, run),
+        ["tokens", path] => with_source(path, tokens),
+        ["ast" | "parse", path] => with_source(path, ast),
+        ["repl"] => repl::run(big_ints),
+        ["explain"] => list_codes(),
+        ["explain", code] => explain(code),
+        ["help" | "-h" | "--help"] => {
+            println!("{USAGE}");
+            ExitCode::SUCCESS
+        }
+        [path] if !path.starts_with('-') && !COMMANDS.contains(path) => with_source(path, run),
+        _ => {
+            eprintln!("{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// remove `flag` from `args`, returning whether it was there
+fn take_flag(args: &mut Vec<&str>, flag: &str) -> bool {
+    let len = args.len();
+    args.retain(|arg| *arg != flag);
+    args.len() != len
+}
+
+/// read the file at `path` and hand its contents to `command`
+fn with_source(path: &str, command: impl Fn(&str, &str) -> ExitCode) -> ExitCode {
+    match std::fs::read_to_string(path) {
+        Ok(code) => command(path, &code),
+        Err(err) => {
+            eprintln!("error: cannot read {path}: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(path: &str, code: &str, big_ints: bool) -> ExitCode {
+    let Some(program) = parse_or_report(path, code) else {
+        return ExitCode::FAILURE;
+    };
+    let mut evaluator = Evaluator::new().with_big_ints(big_ints);
+    match evaluator.eval_program(program) {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(err) => report(path, code, &err),
+    }
+}
+
+fn tokens(_path: &str, code: &str) -> ExitCode {
+    write_stdout(|out| {
+        for token in lex(code) {
+            let lexeme = &code[token.span.clone()];
+            writeln!(
+                out,
+                "{}..{} {:?} {:?}",
+                token.span.start, token.span.end, token.kind, lexeme
+            )?;
+        }
+        Ok(())
+    })
+}
+
+fn ast(path: &str, code: &str) -> ExitCode {
+    match parse_or_report(path, code) {
+        Some(program) => write_stdout(|out| writeln!(out, "{program:#?}")),
+        None => ExitCode::FAILURE,
+    }
+}
+
+/// write output that may be long to stdout, stopping quietly when the
+/// reader goes away, like `jaksel tokens big.jks | head`
+fn write_stdout(write: impl FnOnce(&mut io::StdoutLock) -> io::Result<()>) -> ExitCode {
+    let mut out = io::stdout().lock();
+    match write(&mut out).and_then(|()| out.flush()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: cannot write output: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// print the long description of the error with `code`
+fn explain(code: &str) -> ExitCode {
+    match ErrorKind::from_code(code) {
+        Some(kind) => {
+            print!("{}", kind.explanation());
+            ExitCode::SUCCESS
+        }
+        None => {
+            eprintln!("error: {code} is not an error code, `jaksel explain` lists them");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// print every error code with its one line summary
+fn list_codes() -> ExitCode {
+    for kind in ErrorKind::ALL {
+        println!("{}", kind.explanation().lines().next().unwrap_or_default());
+    }
+    ExitCode::SUCCESS
+}
+
+/// parse `code`, reporting every syntax error in it instead of just the first
+fn parse_or_report(path: &str, code: &str) -> Option<Program> {
+    let (program, errors) = parse_all(code);
+    for (i, err) in errors.iter().enumerate() {
+        if i > 0 {
+            eprintln!();
+        }
+        report(path, code, err);
+    }
+    errors.is_empty().then_some(program)
+}
+
+/// print `err` with the line it points at, in color when stderr is a
+/// terminal and `NO_COLOR` isn't set
+fn report(path: &str, code: &str, err: &Error) -> ExitCode {
+    let color = std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    let renderer = if color {
+        Renderer::colored()
+    } else {
+        Renderer::plain()
+    };
+    eprint!("{}", renderer.render(path, code, err));
+    ExitCode::FAILURE
+}
@@ -0,0 +1,174 @@
+use std::io::{self, BufRead, IsTerminal};
+use std::process::ExitCode;
+
+use rustyline::{DefaultEditor, error::ReadlineError};
+
+use jaksel_lang::{
+    eval::{Evaluator, Value},
+    lexer::{TokenKind, lex},
+};
+
+const PROMPT: &str = "jaksel> ";
+const CONTINUE: &str = "   ...> ";
+
+const HELP: &str = "\
+:ast <code>      print the syntax tree of <code>
+:tokens <code>   print the token stream of <code>
+:env             list the global bindings
+:history         list the inputs of this session
+:help            print this message
+:quit            leave the repl";
+
+struct Repl {
+    evaluator: Evaluator,
+    history: Vec<String>,
+}
+
+/// where input lines come from: a line editor with arrow-key history on a
+/// terminal, plain stdin otherwise so piped scripts print no prompts
+enum Input {
+    Editor(Box<DefaultEditor>),
+    Stdin,
+}
+
+impl Input {
+    fn new() -> Input {
+        if io::stdin().is_terminal()
+            && let Ok(editor) = DefaultEditor::new()
+        {
+            return Input::Editor(Box::new(editor));
+        }
+        Input::Stdin
+    }
+
+    /// read one line including its newline, `None` at the end of input
+    fn read_line(&mut self, prompt: &str) -> io::Result<Option<String>> {
+        match self {
+            Input::Editor(editor) => match editor.readline(prompt) {
+                Ok(line) => Ok(Some(line + "\n")),
+                // ctrl-c drops the pending line like in a shell
+                Err(ReadlineError::Interrupted) => Ok(Some(String::new())),
+                Err(ReadlineError::Eof) => Ok(None),
+                Err(ReadlineError::Io(err)) => Err(err),
+                Err(err) => Err(io::Error::other(err)),
+            },
+            Input::Stdin => {
+                let mut line = String::new();
+                match io::stdin().lock().read_line(&mut line)? {
+                    0 => Ok(None),
+                    _ => Ok(Some(line)),
+                }
+            }
+        }
+    }
+
+    fn add_history(&mut self, entry: &str) {
+        if let Input::Editor(editor) = self {
+            editor.add_history_entry(entry).ok();
+        }
+    }
+}
+
+/// read-eval-print loop over stdin, keeping one `Evaluator` alive so bindings
+/// from earlier inputs stay visible
+pub fn run(big_ints: bool) -> ExitCode {
+    let mut repl = Repl {
+        evaluator: Evaluator::new().with_big_ints(big_ints),
+        history: Vec::new(),
+    };
+    let mut input = Input::new();
+    let mut buffer = String::new();
+
+    loop {
+        let prompt = if buffer.is_empty() { PROMPT } else { CONTINUE };
+        let line = match input.read_line(prompt) {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(err) => {
+                eprintln!("error: cannot read input: {err}");
+                return ExitCode::FAILURE;
+            }
+        };
+
+        if buffer.is_empty() && line.trim_start().starts_with(':') {
+            input.add_history(line.trim());
+            if !repl.command(line.trim()) {
+                break;
+            }
+            continue;
+        }
+
+        buffer.push_str(&line);
+        if unclosed_braces(&buffer) {
+            continue;
+        }
+
+        let code = std::mem::take(&mut buffer);
+        if code.trim().is_empty() {
+            continue;
+        }
+        input.add_history(code.trim_end());
+        repl.history.push(code.trim_end().to_owned());
+        repl.eval(&code);
+    }
+
+    ExitCode::SUCCESS
+}
+
+impl Repl {
+    fn eval(&mut self, input: &str) {
+        let Some(program) = crate::parse_or_report("<repl>", input) else {
+            return;
+        };
+        match self.evaluator.eval_program(program) {
+            Ok(Value::None) => {}
+            Ok(value) => println!("{value}"),
+            Err(err) => {
+                crate::report("<repl>", input, &err);
+            }
+        }
+    }
+
+    /// run a meta-command, returning `false` when the repl should exit
+    fn command(&mut self, line: &str) -> bool {
+        let (name, arg) = line.split_once(' ').unwrap_or((line, ""));
+        let arg = arg.trim();
+        match name {
+            ":ast" => {
+                if let Some(program) = crate::parse_or_report("<repl>", arg) {
+                    println!("{program:#?}");
+                }
+            }
+            ":tokens" => {
+                crate::tokens("<repl>", arg);
+            }
+            ":env" => {
+                for (name, value) in self.evaluator.env().borrow().bindings() {
+                    println!("{name} = {value}");
+                }
+            }
+            ":history" => {
+                for (i, input) in self.history.iter().enumerate() {
+                    println!("{:>4}  {input}", i + 1);
+                }
+            }
+            ":help" => println!("{HELP}"),
+            ":quit" | ":q" => return false,
+            _ => eprintln!("unknown command {name}, try :help"),
+        }
+        true
+    }
+}
+
+/// whether `code` opens more blocks or lists than it closes, so the input
+/// continues on the next line
+fn unclosed_braces(code: &str) -> bool {
+    let depth = lex(code)
+        .iter()
+        .fold(0i32, |depth, token| match token.kind {
+            TokenKind::TOK_LBRACE | TokenKind::TOK_LBRACKET => depth + 1,
+            TokenKind::TOK_RBRACE | TokenKind::TOK_RBRACKET => depth - 1,
+            _ => depth,
+        });
+    depth > 0
+}
@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+
+use crate::ast::*;
+use crate::error::{ErrorKind, Result, error};
+use crate::span::Span;
+
+/// static checks over a parsed program, run before evaluation so mistakes
+/// are reported even in code that never runs
+pub fn check_program(program: &Program) -> Result<()> {
+    let mut checker = Checker {
+        scopes: vec![HashMap::new()],
+    };
+    checker.hoist(&program.body)?;
+    for stmt in &program.body {
+        checker.stmt(stmt)?;
+    }
+    if let Some(tail) = &program.tail {
+        checker.expr(tail)?;
+    }
+    Ok(())
+}
+
+struct Checker {
+    /// the names declared in each scope, mapped to where they were declared
+    /// if they are constant
+    scopes: Vec<HashMap<String, Option<Span>>>,
+}
+
+impl Checker {
+    fn stmt(&mut self, stmt: &Stmt) -> Result<()> {
+        match stmt {
+            Stmt::Fn(f) => self.function(&f.params, &f.body),
+            Stmt::Record(r) => {
+                for method in &r.methods {
+                    self.function(&method.params, &method.body)?;
+                }
+                Ok(())
+            }
+            Stmt::Enum(e) => {
+                for variant in &e.variants {
+                    self.declare(&variant.name, None);
+                }
+                Ok(())
+            }
+            Stmt::Let(l) => {
+                self.expr(&l.value)?;
+                // constants are hoisted, so only one declared further up counts
+                if let Some(Some(declared)) = self.scopes.last().unwrap().get(&l.name)
+                    && declared.start < l.span.start
+                {
+                    return Err(error(
+                        ErrorKind::Constant,
+                        l.span.clone(),
+                        format!("cannot redeclare constant {}", l.name),
+                    )
+                    .with_label(declared.clone(), "first declared here"));
+                }
+                self.declare(&l.name, l.constant.then(|| l.span.clone()));
+                Ok(())
+            }
+            Stmt::Assign(a) => {
+                self.expr(&a.target)?;
+                self.expr(&a.value)?;
+                if let Expr::Identifier(ident) = &a.target
+                    && let Some(declared) = self.constant(&ident.name)
+                {
+                    return Err(error(
+                        ErrorKind::Constant,
+                        a.span.clone(),
+                        format!("cannot assign to constant {}", ident.name),
+                    )
+                    .with_label(declared, "declared with seriously here")
+                    .with_note("declare it with literally to allow reassigning it"));
+                }
+                Ok(())
+            }
+            Stmt::While(w) => {
+                self.expr(&w.cond)?;
+                self.block(&w.body)
+            }
+            Stmt::For(f) => {
+                self.expr(&f.iter)?;
+                if let Some(end) = &f.end {
+                    self.expr(end)?;
+                }
+                self.function(std::slice::from_ref(&f.name), &f.body)
+            }
+            Stmt::Return(r) => match &r.value {
+                Some(value) => self.expr(value),
+                None => Ok(()),
+            },
+            Stmt::Break(_) | Stmt::Continue(_) | Stmt::Error(_) => Ok(()),
+            Stmt::Expr(e) => self.expr(e),
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Result<()> {
+        match expr {
+            Expr::If(expr_if) => {
+                for branch in &expr_if.branches {
+                    self.expr(&branch.cond)?;
+                    self.block(&branch.body)?;
+                }
+                if let Some(tail) = &expr_if.tail {
+                    self.block(tail)?;
+                }
+                Ok(())
+            }
+            Expr::Block(b) => self.block(b),
+            Expr::Template(t) => {
+                for part in &t.parts {
+                    if let TemplatePart::Expr(e) = part {
+                        self.expr(e)?;
+                    }
+                }
+                Ok(())
+            }
+            Expr::Call(call) => {
+                self.expr(&call.callee)?;
+                for arg in &call.args {
+                    self.expr(arg)?;
+                }
+                Ok(())
+            }
+            Expr::Binary(b) => {
+                self.expr(&b.lhs)?;
+                self.expr(&b.rhs)
+            }
+            Expr::Unary(u) => self.expr(&u.rhs),
+            Expr::Lambda(l) => self.function(&l.params, &l.body),
+            Expr::List(l) => {
+                for item in &l.items {
+                    self.expr(item)?;
+                }
+                Ok(())
+            }
+            Expr::Map(m) => {
+                for entry in &m.entries {
+                    self.expr(&entry.key)?;
+                    self.expr(&entry.value)?;
+                }
+                Ok(())
+            }
+            Expr::Field(f) => self.expr(&f.target),
+            Expr::Match(m) => {
+                self.expr(&m.value)?;
+                for arm in &m.arms {
+                    let mut bindings = Vec::new();
+                    self.pattern(&arm.pattern, &mut bindings)?;
+                    self.scopes
+                        .push(bindings.into_iter().map(|b| (b, None)).collect());
+                    let result = (|| {
+                        if let Some(guard) = &arm.guard {
+                            self.expr(guard)?;
+                        }
+                        self.expr(&arm.body)
+                    })();
+                    self.scopes.pop();
+                    result?;
+                }
+                Ok(())
+            }
+            Expr::Index(i) => {
+                self.expr(&i.target)?;
+                self.expr(&i.index)
+            }
+            Expr::Slice(sl) => {
+                self.expr(&sl.target)?;
+                for bound in [&sl.start, &sl.end].into_iter().flatten() {
+                    self.expr(bound)?;
+                }
+                Ok(())
+            }
+            Expr::Str(_)
+            | Expr::Int(_)
+            | Expr::BigInt(_)
+            | Expr::Float(_)
+            | Expr::Bool(_)
+            | Expr::None(_)
+            | Expr::Identifier(_) => Ok(()),
+        }
+    }
+
+    /// check the literals in `pattern` and collect the names it binds
+    fn pattern(&mut self, pattern: &Pattern, bindings: &mut Vec<String>) -> Result<()> {
+        match pattern {
+            Pattern::Wildcard => Ok(()),
+            Pattern::Binding(name) => {
+                bindings.push(name.clone());
+                Ok(())
+            }
+            Pattern::Literal(e) => self.expr(e),
+            Pattern::Variant(v) => {
+                for field in v.fields.iter().flatten() {
+                    self.pattern(field, bindings)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn block(&mut self, block: &Block) -> Result<()> {
+        self.scopes.push(HashMap::new());
+        let result = self.block_body(block);
+        self.scopes.pop();
+        result
+    }
+
+    fn block_body(&mut self, block: &Block) -> Result<()> {
+        self.hoist(&block.body)?;
+        for stmt in &block.body {
+            self.stmt(stmt)?;
+        }
+        if let Some(tail) = &block.tail {
+            self.expr(tail)?;
+        }
+        Ok(())
+    }
+
+    /// check `body` in a scope where `params` are declared, like a function
+    /// body or a loop body
+    fn function(&mut self, params: &[String], body: &Block) -> Result<()> {
+        self.scopes
+            .push(params.iter().map(|p| (p.clone(), None)).collect());
+        let result = self.block(body);
+        self.scopes.pop();
+        result
+    }
+
+    /// declare the constants, functions and records of `body` before walking
+    /// it, so a function body sees a constant declared below the function
+    fn hoist(&mut self, body: &[Stmt]) -> Result<()> {
+        let scope = self.scopes.last_mut().unwrap();
+        for stmt in body {
+            if let Stmt::Let(l) = stmt
+                && l.constant
+            {
+                scope
+                    .entry(l.name.clone())
+                    .or_insert_with(|| Some(l.span.clone()));
+            }
+        }
+        let mut records: HashMap<&str, &Span> = HashMap::new();
+        for stmt in body {
+            let (name, span) = match stmt {
+                Stmt::Fn(f) => (&f.name, &f.span),
+                Stmt::Record(r) => (&r.name, &r.span),
+                _ => continue,
+            };
+            if let Stmt::Record(r) = stmt
+                && let Some(first) = records.insert(&r.name, &r.span)
+            {
+                return Err(error(
+                    ErrorKind::Syntax,
+                    r.span.clone(),
+                    format!("record {name} is declared twice"),
+                )
+                .with_label(first.clone(), "first declared here"));
+            }
+            if let Some(Some(declared)) = self.scopes.last().unwrap().get(name) {
+                return Err(error(
+                    ErrorKind::Constant,
+                    span.clone(),
+                    format!("cannot redeclare constant {name}"),
+                )
+                .with_label(declared.clone(), "declared with seriously here"));
+            }
+        }
+        for stmt in body {
+            match stmt {
+                Stmt::Fn(f) => self.declare(&f.name, None),
+                Stmt::Record(r) => self.declare(&r.name, None),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn declare(&mut self, name: &str, constant: Option<Span>) {
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(name.to_owned(), constant);
+    }
+
+    /// where the nearest declaration of `name` was if it is a constant, names
+    /// that aren't declared in the program (like natives) are not constant
+    fn constant(&self, name: &str) -> Option<Span> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .cloned()
+            .flatten()
+    }
+}
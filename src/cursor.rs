@@ -1,5 +1,5 @@
 use crate::{
-    error::{Result, error},
+    error::{Error, ErrorKind, Result, error},
     lexer::{Token, TokenKind},
 };
 
@@ -7,6 +7,12 @@ pub struct Cursor<'src> {
     pub code: &'src str,
     pub tokens: Vec<Token>,
     pub position: usize,
+    /// how many loops enclose the current position, reset inside functions
+    pub loop_depth: usize,
+    /// how many functions enclose the current position
+    pub fn_depth: usize,
+    /// the syntax errors recovered from so far
+    pub errors: Vec<Error>,
 }
 
 impl<'src> Cursor<'src> {
@@ -15,6 +21,9 @@ impl<'src> Cursor<'src> {
             position: 0,
             code,
             tokens,
+            loop_depth: 0,
+            fn_depth: 0,
+            errors: Vec::new(),
         }
     }
     pub fn advance(&mut self) {
@@ -37,7 +46,8 @@ impl<'src> Cursor<'src> {
         self.tokens[self.position - 1].clone()
     }
     pub fn peek(&self) -> Token {
-        self.tokens[self.position + 1].clone()
+        let position = (self.position + 1).min(self.tokens.len() - 1);
+        self.tokens[position].clone()
     }
     pub fn kind(&self) -> TokenKind {
         self.current().kind
@@ -69,6 +79,7 @@ impl<'src> Cursor<'src> {
             Ok(current)
         } else {
             Err(error(
+                ErrorKind::Syntax,
                 current.span,
                 format!("expected: {:?}, found {:?}", kind, current.kind),
             ))
@@ -0,0 +1,300 @@
+use std::fmt::Write as _;
+
+use crate::error::Error;
+use crate::span::Span;
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// the start of every line of a source, to turn byte offsets into lines and
+/// columns
+pub struct LineIndex<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        LineIndex {
+            source,
+            line_starts,
+        }
+    }
+
+    /// 1-based line and column of a byte offset, columns count characters
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.source.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let col = self.source[self.line_starts[line]..offset].chars().count();
+        (line + 1, col + 1)
+    }
+
+    /// the text of a 1-based line, without its line break
+    pub fn line(&self, line: usize) -> &'a str {
+        let start = self.line_starts[line - 1];
+        let end = self
+            .line_starts
+            .get(line)
+            .map_or(self.source.len(), |&next| next - 1);
+        self.source[start..end].trim_end_matches('\r')
+    }
+}
+
+/// turns errors into text like
+///
+/// ```text
+/// error[JKS0003]: undefined variable: x
+///  --> main.jks:1:7
+///   |
+/// 1 | spill(x) wkwk
+///   |       ^
+/// ```
+pub struct Renderer {
+    color: bool,
+}
+
+/// one underlined span, the primary one has no message of its own
+struct Mark<'a> {
+    span: Span,
+    message: Option<&'a str>,
+    primary: bool,
+}
+
+impl Renderer {
+    pub fn plain() -> Self {
+        Renderer { color: false }
+    }
+
+    /// with ANSI colors, for terminals
+    pub fn colored() -> Self {
+        Renderer { color: true }
+    }
+
+    pub fn render(&self, path: &str, source: &str, err: &Error) -> String {
+        let index = LineIndex::new(source);
+        let mut marks = vec![Mark {
+            span: err.span(),
+            message: None,
+            primary: true,
+        }];
+        marks.extend(err.labels().iter().map(|label| Mark {
+            span: label.span.clone(),
+            message: Some(&label.message),
+            primary: false,
+        }));
+        let lines: Vec<usize> = marks
+            .iter()
+            .map(|mark| index.line_col(mark.span.start as usize).0)
+            .collect();
+        let gutter = lines.iter().max().unwrap().to_string().len();
+        let pad = " ".repeat(gutter);
+
+        let mut out = String::new();
+        let (line, col) = index.line_col(err.span().start());
+        writeln!(
+            out,
+            "{}: {}",
+            self.paint(RED, &format!("error[{}]", err.code())),
+            self.paint(BOLD, err.message())
+        )
+        .ok();
+        writeln!(out, "{pad}{} {path}:{line}:{col}", self.paint(BLUE, "-->")).ok();
+        writeln!(out, "{pad} {}", self.paint(BLUE, "|")).ok();
+
+        let mut shown: Vec<usize> = lines.clone();
+        shown.sort();
+        shown.dedup();
+        for line in shown {
+            let text = index.line(line);
+            let number = format!("{line:>gutter$}");
+            writeln!(out, "{} {text}", self.paint(BLUE, &format!("{number} |"))).ok();
+            for (mark, _) in marks.iter().zip(&lines).filter(|(_, l)| **l == line) {
+                let underline = self.underline(&index, text, line, mark);
+                writeln!(out, "{pad} {} {underline}", self.paint(BLUE, "|")).ok();
+            }
+        }
+
+        if !err.notes().is_empty() || !err.backtrace().is_empty() {
+            writeln!(out, "{pad} {}", self.paint(BLUE, "|")).ok();
+        }
+        for note in err.notes() {
+            writeln!(out, "{pad} {} {note}", self.paint(BLUE, "= note:")).ok();
+        }
+        for (i, line) in backtrace(&index, path, err).iter().enumerate() {
+            let marker = if i == 0 { "=" } else { " " };
+            writeln!(out, "{pad} {} {line}", self.paint(BLUE, marker)).ok();
+        }
+        out
+    }
+
+    /// the carets under `mark`, clipped to the end of its first line
+    fn underline(&self, index: &LineIndex, text: &str, line: usize, mark: &Mark) -> String {
+        let (_, col) = index.line_col(mark.span.start as usize);
+        let (end_line, end_col) = index.line_col(mark.span.end as usize);
+        let width = text.chars().count();
+        let end_col = if end_line > line { width + 1 } else { end_col };
+        let len = end_col.saturating_sub(col).max(1);
+
+        // keep tabs so the carets line up with the text above them
+        let indent: String = text
+            .chars()
+            .take(col - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let (caret, style) = if mark.primary {
+            ('^', RED)
+        } else {
+            ('-', BLUE)
+        };
+        let mut underline = caret.to_string().repeat(len);
+        if let Some(message) = mark.message {
+            underline = format!("{underline} {message}");
+        }
+        format!("{indent}{}", self.paint(style, &underline))
+    }
+
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.color {
+            format!("{style}{text}{RESET}")
+        } else {
+            text.to_owned()
+        }
+    }
+}
+
+/// the functions an error happened in, like
+///
+/// ```text
+/// di dalam tambahin (main.jks:2:5)
+/// dipanggil dari hitung (main.jks:5:3)
+/// dipanggil dari main.jks:8:1
+/// ```
+///
+/// where a call repeated many times, like in deep recursion, is shown once
+fn backtrace(index: &LineIndex, path: &str, err: &Error) -> Vec<String> {
+    let frames = err.backtrace();
+    let Some(first) = frames.first() else {
+        return Vec::new();
+    };
+    let location = |span: Span| {
+        let (line, col) = index.line_col(span.start());
+        format!("{path}:{line}:{col}")
+    };
+
+    let mut lines = vec![format!(
+        "di dalam {} ({})",
+        first.function,
+        location(err.span())
+    )];
+    let callers = frames.iter().skip(1).map(|frame| Some(&frame.function));
+    let calls: Vec<String> = frames
+        .iter()
+        .zip(callers.chain([None]))
+        .map(|(frame, caller)| match caller {
+            Some(caller) => format!("dipanggil dari {caller} ({})", location(frame.span.clone())),
+            None => format!("dipanggil dari {}", location(frame.span.clone())),
+        })
+        .collect();
+    let mut i = 0;
+    while i < calls.len() {
+        let repeats = calls[i..]
+            .iter()
+            .take_while(|call| **call == calls[i])
+            .count();
+        if repeats > 1 {
+            lines.push(format!("{} [{repeats} kali]", calls[i]));
+        } else {
+            lines.push(calls[i].clone());
+        }
+        i += repeats;
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{ErrorKind, Frame, error};
+
+    #[test]
+    fn test_line_col() {
+        let index = LineIndex::new("ab\ncdé\n\nf");
+        assert_eq!(index.line_col(0), (1, 1));
+        assert_eq!(index.line_col(2), (1, 3));
+        assert_eq!(index.line_col(3), (2, 1));
+        assert_eq!(index.line_col(7), (2, 4));
+        assert_eq!(index.line_col(8), (3, 1));
+        assert_eq!(index.line_col(9), (4, 1));
+        assert_eq!(index.line_col(100), (4, 2));
+        assert_eq!(index.line(2), "cdé");
+        assert_eq!(index.line(3), "");
+    }
+
+    #[test]
+    fn test_render() {
+        let source = "seriously x itu 1 wkwk\n\tx itu 2 wkwk\n";
+        let err = error(ErrorKind::Constant, 24..25, "cannot assign to constant x")
+            .with_label(10..11, "declared as a constant here")
+            .with_note("use `literally` for values that change");
+        assert_eq!(
+            Renderer::plain().render("main.jks", source, &err),
+            "\
+error[JKS0004]: cannot assign to constant x
+ --> main.jks:2:2
+  |
+1 | seriously x itu 1 wkwk
+  |           - declared as a constant here
+2 | \tx itu 2 wkwk
+  | \t^
+  |
+  = note: use `literally` for values that change
+"
+        );
+    }
+
+    #[test]
+    fn test_render_multiline_span() {
+        let source = "kalo 1 {\n  2\n}";
+        let err = error(ErrorKind::Name, 5..14, "oops");
+        let out = Renderer::plain().render("a.jks", source, &err);
+        assert!(out.contains("1 | kalo 1 {\n  |      ^^^\n"), "{out}");
+    }
+
+    #[test]
+    fn test_render_backtrace() {
+        let source = "so about f(n) {\n  f(n kurang 1)\n}\nf(2)\n";
+        let frame = |function: &str, span: std::ops::Range<usize>| Frame {
+            function: function.to_owned(),
+            span: span.into(),
+        };
+        let err = error(ErrorKind::Name, 18..30, "oops").with_backtrace(vec![
+            frame("f", 18..30),
+            frame("f", 18..30),
+            frame("f", 34..38),
+        ]);
+        let out = Renderer::plain().render("a.jks", source, &err);
+        assert!(
+            out.ends_with(
+                "  |
+  = di dalam f (a.jks:2:3)
+    dipanggil dari f (a.jks:2:3) [2 kali]
+    dipanggil dari a.jks:4:1
+"
+            ),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn test_render_colored() {
+        let err = error(ErrorKind::Name, 0..1, "oops");
+        let out = Renderer::colored().render("a.jks", "x", &err);
+        assert!(out.starts_with("\x1b[1;31merror[JKS0003]\x1b[0m: \x1b[1moops\x1b[0m\n"));
+        assert!(out.contains("\x1b[1;31m^\x1b[0m"));
+    }
+}
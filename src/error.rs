@@ -1,16 +1,237 @@
 use crate::span::Span;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, thiserror::Error)]
+#[error("{message}")]
 pub struct Error {
+    kind: ErrorKind,
     span: Span,
     message: String,
+    labels: Vec<Label>,
+    notes: Vec<String>,
+    /// the functions that were running when a runtime error happened,
+    /// innermost first
+    backtrace: Vec<Frame>,
 }
 
-pub fn error(span: impl Into<Span>, message: impl Into<String>) -> Error {
+/// what went wrong, in broad strokes, for code that needs to tell errors
+/// apart without looking at their messages
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// source text that isn't a token, like an unterminated string
+    Lex,
+    /// tokens in an order the grammar doesn't allow
+    Syntax,
+    /// a variable or field that doesn't exist
+    Name,
+    /// writing to a `seriously` binding
+    Constant,
+    /// a value of the wrong type for an operation
+    Type,
+    /// the wrong number of arguments, fields or pattern fields
+    Arity,
+    /// int overflow, division by zero and bad shifts
+    Arithmetic,
+    /// a list index out of bounds or a missing map key
+    Index,
+    /// a value of the right type that still can't be used, like converting
+    /// `"abc"` to an int
+    Value,
+    /// a `cocokin` where no arm matches
+    Match,
+    /// a limit of the evaluator, like the call depth of a recursion that
+    /// never stops
+    Limit,
+}
+
+impl ErrorKind {
+    pub const ALL: [ErrorKind; 11] = [
+        ErrorKind::Lex,
+        ErrorKind::Syntax,
+        ErrorKind::Name,
+        ErrorKind::Constant,
+        ErrorKind::Type,
+        ErrorKind::Arity,
+        ErrorKind::Arithmetic,
+        ErrorKind::Index,
+        ErrorKind::Value,
+        ErrorKind::Match,
+        ErrorKind::Limit,
+    ];
+
+    /// the code printed with the error, these never change meaning so they
+    /// can be searched for and matched on
+    pub fn code(self) -> &'static str {
+        match self {
+            ErrorKind::Lex => "JKS0001",
+            ErrorKind::Syntax => "JKS0002",
+            ErrorKind::Name => "JKS0003",
+            ErrorKind::Constant => "JKS0004",
+            ErrorKind::Type => "JKS0005",
+            ErrorKind::Arity => "JKS0006",
+            ErrorKind::Arithmetic => "JKS0007",
+            ErrorKind::Index => "JKS0008",
+            ErrorKind::Value => "JKS0009",
+            ErrorKind::Match => "JKS0010",
+            ErrorKind::Limit => "JKS0011",
+        }
+    }
+
+    /// the kind with `code`, ignoring case
+    pub fn from_code(code: &str) -> Option<ErrorKind> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.code().eq_ignore_ascii_case(code))
+    }
+
+    /// a longer description of the error with examples, shown by
+    /// `jaksel explain`
+    pub fn explanation(self) -> &'static str {
+        match self {
+            ErrorKind::Lex => include_str!("explanations/JKS0001.md"),
+            ErrorKind::Syntax => include_str!("explanations/JKS0002.md"),
+            ErrorKind::Name => include_str!("explanations/JKS0003.md"),
+            ErrorKind::Constant => include_str!("explanations/JKS0004.md"),
+            ErrorKind::Type => include_str!("explanations/JKS0005.md"),
+            ErrorKind::Arity => include_str!("explanations/JKS0006.md"),
+            ErrorKind::Arithmetic => include_str!("explanations/JKS0007.md"),
+            ErrorKind::Index => include_str!("explanations/JKS0008.md"),
+            ErrorKind::Value => include_str!("explanations/JKS0009.md"),
+            ErrorKind::Match => include_str!("explanations/JKS0010.md"),
+            ErrorKind::Limit => include_str!("explanations/JKS0011.md"),
+        }
+    }
+}
+
+/// a secondary span pointed at when rendering an error, with a short
+/// explanation
+#[derive(Clone, Debug, PartialEq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// a call to a jaksel function that was running when an error happened
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    pub function: String,
+    /// where the function was called from
+    pub span: Span,
+}
+
+impl Error {
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+
+    pub fn span(&self) -> Span {
+        self.span.clone()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    pub fn notes(&self) -> &[String] {
+        &self.notes
+    }
+
+    pub fn backtrace(&self) -> &[Frame] {
+        &self.backtrace
+    }
+
+    pub fn with_label(mut self, span: impl Into<Span>, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span: span.into(),
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_backtrace(mut self, backtrace: Vec<Frame>) -> Self {
+        self.backtrace = backtrace;
+        self
+    }
+}
+
+pub fn error(kind: ErrorKind, span: impl Into<Span>, message: impl Into<String>) -> Error {
     Error {
+        kind,
         span: span.into(),
         message: message.into(),
+        labels: Vec::new(),
+        notes: Vec::new(),
+        backtrace: Vec::new(),
     }
 }
 
 pub type Result<V> = std::result::Result<V, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes() {
+        for kind in ErrorKind::ALL {
+            assert_eq!(ErrorKind::from_code(kind.code()), Some(kind));
+            assert!(
+                kind.explanation()
+                    .starts_with(&format!("{}: ", kind.code())),
+                "{kind:?}"
+            );
+        }
+        assert_eq!(ErrorKind::from_code("jks0005"), Some(ErrorKind::Type));
+        assert_eq!(ErrorKind::from_code("JKS9999"), None);
+    }
+
+    /// the indented code blocks of an explanation
+    fn examples(explanation: &str) -> Vec<String> {
+        let mut blocks: Vec<String> = Vec::new();
+        let mut in_block = false;
+        for line in explanation.lines() {
+            match line.strip_prefix("    ") {
+                Some(code) if in_block => {
+                    let block = blocks.last_mut().unwrap();
+                    block.push('\n');
+                    block.push_str(code);
+                }
+                Some(code) => blocks.push(code.to_owned()),
+                None => {}
+            }
+            // a blank line inside a block doesn't end it
+            in_block = line.starts_with("    ") || (in_block && line.is_empty());
+        }
+        blocks
+    }
+
+    #[test]
+    fn test_explanation_examples() {
+        let run = |code: &str| {
+            let program = crate::parse(code)?;
+            crate::eval::Evaluator::new().eval_program(program)
+        };
+        for kind in ErrorKind::ALL {
+            let examples = examples(kind.explanation());
+            let [erroneous, fixed] = &examples[..] else {
+                panic!("{kind:?} should have 2 examples, has {examples:?}");
+            };
+            assert_eq!(run(erroneous).map_err(|err| err.kind()).err(), Some(kind));
+            if let Err(err) = run(fixed) {
+                panic!("the fixed {kind:?} example fails: {err}");
+            }
+        }
+    }
+}
@@ -1,62 +1,320 @@
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::{cell::RefCell, rc::Rc};
 
 use crate::span::Span;
 use crate::{
     ast::*,
-    error::{Result, error},
+    bigint::BigInt,
+    error::{Error, ErrorKind, Frame, Result, error},
 };
 
-pub type NativeFn = fn(&[Value]) -> Result<Value>;
+/// a function written in Rust, given the arguments and the span of the call
+pub type NativeFn = fn(&[Value], &Span) -> Result<Value>;
 
 #[derive(Clone, Debug)]
 pub enum Value {
     Int(i64),
+    /// an int outside of the `i64` range, only made with big ints enabled
+    BigInt(Rc<BigInt>),
+    Float(f64),
     Str(String),
     Bool(bool),
+    /// shared, so a list mutated through one binding changes in all of them
+    List(Rc<RefCell<Vec<Value>>>),
+    /// shared like lists
+    Map(Rc<RefCell<Map>>),
+    /// a type declared with `bentuk`, calling it builds an instance
+    Record(Rc<RecordType>),
+    Instance(Rc<RefCell<Instance>>),
+    /// a variant declared with fields in `jenis`, calling it builds the value
+    Constructor(Rc<VariantType>),
+    Variant(Rc<Variant>),
     Function(Rc<FunctionValue>),
-    NativeFunction { name: String, func: NativeFn },
+    NativeFunction {
+        name: String,
+        func: NativeFn,
+    },
     None,
 }
 
+/// the values that can be used as map keys
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MapKey {
+    Int(i64),
+    Str(String),
+}
+
+/// a map that remembers the order its keys were inserted in
+#[derive(Clone, Debug, Default)]
+pub struct Map {
+    entries: Vec<(MapKey, Value)>,
+    /// position of each key in `entries`
+    positions: HashMap<MapKey, usize>,
+}
+
+#[derive(Debug)]
+pub struct RecordType {
+    pub name: String,
+    pub fields: Vec<String>,
+    pub methods: HashMap<String, Rc<FunctionValue>>,
+}
+
+/// a value of a record type, with one value per field of the type
+#[derive(Debug)]
+pub struct Instance {
+    pub record: Rc<RecordType>,
+    pub values: Vec<Value>,
+}
+
+/// one variant of a type declared with `jenis`
+#[derive(Debug)]
+pub struct VariantType {
+    pub enum_name: String,
+    pub name: String,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Variant {
+    pub variant: Rc<VariantType>,
+    pub values: Vec<Value>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Environment {
     values: HashMap<String, Value>,
+    /// names in `values` declared with `seriously`
+    constants: HashSet<String>,
     parent: Option<Rc<RefCell<Environment>>>,
 }
 
-#[derive(Clone, Debug)]
+/// why `Environment::assign` failed
+#[derive(Debug, PartialEq)]
+pub enum AssignError {
+    Undefined,
+    Constant,
+}
+
+#[derive(Clone)]
 pub struct FunctionValue {
     pub name: String,
     pub params: Vec<String>,
     pub body: Block,
-    pub closure: Environment,
+    /// the scope the function was declared in, shared so the function sees
+    /// itself and anything declared there later
+    pub closure: Rc<RefCell<Environment>>,
+}
+
+/// non-local control flow unwinding the evaluator, next to errors
+#[derive(Debug)]
+enum Unwind {
+    Error(Error),
+    Return(Value),
+    Break(Span),
+    Continue(Span),
+}
+
+impl From<Error> for Unwind {
+    fn from(err: Error) -> Self {
+        Unwind::Error(err)
+    }
 }
 
+type Flow<V> = std::result::Result<V, Unwind>;
+
+/// how many jaksel calls may be nested before the evaluator gives up, so
+/// runaway recursion is an error instead of a crash
+pub const MAX_CALL_DEPTH: usize = 1000;
+
 pub struct Evaluator {
     env: Rc<RefCell<Environment>>,
+    /// whether an overflowing int becomes a `Value::BigInt` instead of an
+    /// error
+    big_ints: bool,
+    /// the jaksel functions being called, outermost first
+    call_stack: Vec<Frame>,
+}
+
+// the closure is left out, it usually contains the function itself
+impl fmt::Debug for FunctionValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FunctionValue")
+            .field("name", &self.name)
+            .field("params", &self.params)
+            .finish_non_exhaustive()
+    }
 }
 
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
+        self.eq_seen(other, &mut Vec::new())
+    }
+}
+
+/// the address of a shared value, to spot values that contain themselves
+type Ptr = *const ();
+
+impl Value {
+    /// `self == other`, where `seen` holds the pairs of collections being
+    /// compared further up, which are taken to be equal so cycles end
+    fn eq_seen(&self, other: &Self, seen: &mut Vec<(Ptr, Ptr)>) -> bool {
+        let pair = self.ptr().zip(other.ptr());
+        if let Some((l, r)) = pair
+            && (l == r || seen.contains(&(l, r)))
+        {
+            return true;
+        }
+
+        seen.extend(pair);
+        let eq_all = |l: &[Value], r: &[Value], seen: &mut Vec<(Ptr, Ptr)>| {
+            l.len() == r.len() && l.iter().zip(r).all(|(l, r)| l.eq_seen(r, seen))
+        };
+        let eq = match (self, other) {
             (Self::Bool(l), Self::Bool(r)) => l == r,
             (Self::Int(l), Self::Int(r)) => l == r,
+            (Self::BigInt(l), Self::BigInt(r)) => l == r,
+            (Self::Float(l), Self::Float(r)) => l == r,
+            (Self::Int(l), Self::Float(r)) | (Self::Float(r), Self::Int(l)) => *l as f64 == *r,
+            (Self::BigInt(l), Self::Float(r)) | (Self::Float(r), Self::BigInt(l)) => {
+                l.to_f64() == *r
+            }
             (Self::Str(l), Self::Str(r)) => l == r,
+            (Self::List(l), Self::List(r)) => eq_all(&l.borrow(), &r.borrow(), seen),
+            (Self::Map(l), Self::Map(r)) => {
+                let (l, r) = (l.borrow(), r.borrow());
+                l.len() == r.len()
+                    && l.iter().all(|(key, value)| {
+                        r.get(key).is_some_and(|other| value.eq_seen(other, seen))
+                    })
+            }
+            (Self::Record(l), Self::Record(r)) => Rc::ptr_eq(l, r),
+            (Self::Instance(l), Self::Instance(r)) => {
+                let (l, r) = (l.borrow(), r.borrow());
+                Rc::ptr_eq(&l.record, &r.record) && eq_all(&l.values, &r.values, seen)
+            }
+            (Self::Constructor(l), Self::Constructor(r)) => Rc::ptr_eq(l, r),
+            (Self::Variant(l), Self::Variant(r)) => {
+                Rc::ptr_eq(&l.variant, &r.variant) && eq_all(&l.values, &r.values, seen)
+            }
+            (Self::Function(l), Self::Function(r)) => Rc::ptr_eq(l, r),
             (Self::NativeFunction { name: l, .. }, Self::NativeFunction { name: r, .. }) => l == r,
             (Self::None, Self::None) => true,
             _ => false,
+        };
+        if pair.is_some() {
+            seen.pop();
         }
+        eq
     }
-}
 
-impl fmt::Display for Value {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    /// the shared value behind a collection, record instance or variant
+    fn ptr(&self) -> Option<Ptr> {
+        match self {
+            Value::List(items) => Some(Rc::as_ptr(items).cast()),
+            Value::Map(map) => Some(Rc::as_ptr(map).cast()),
+            Value::Instance(instance) => Some(Rc::as_ptr(instance).cast()),
+            Value::Variant(v) => Some(Rc::as_ptr(v).cast()),
+            _ => None,
+        }
+    }
+
+    /// write the value, with `seen` holding the values being written further
+    /// up so one that contains itself is shortened to `[...]`
+    fn fmt_seen(&self, f: &mut fmt::Formatter<'_>, seen: &mut Vec<Ptr>) -> fmt::Result {
+        if let Some(ptr) = self.ptr() {
+            if seen.contains(&ptr) {
+                return match self {
+                    Value::Instance(instance) => {
+                        write!(f, "{}(...)", instance.borrow().record.name)
+                    }
+                    Value::Variant(v) => write!(f, "{}(...)", v.variant.name),
+                    _ => write!(f, "[...]"),
+                };
+            }
+            seen.push(ptr);
+        }
+        let result = self.fmt_inner(f, seen);
+        if self.ptr().is_some() {
+            seen.pop();
+        }
+        result
+    }
+
+    /// like `fmt_seen`, quoting strings the way `repr` does
+    fn fmt_repr(&self, f: &mut fmt::Formatter<'_>, seen: &mut Vec<Ptr>) -> fmt::Result {
+        match self {
+            Value::Str(s) => write!(f, "{s:?}"),
+            value => value.fmt_seen(f, seen),
+        }
+    }
+
+    fn fmt_inner(&self, f: &mut fmt::Formatter<'_>, seen: &mut Vec<Ptr>) -> fmt::Result {
         match self {
             Value::Int(i) => write!(f, "{i}"),
+            Value::BigInt(i) => write!(f, "{i}"),
+            // `{:?}` keeps the `.0` of whole floats, so they don't read as ints
+            Value::Float(x) => write!(f, "{x:?}"),
             Value::Str(s) => write!(f, "{s}"),
             Value::Bool(b) => write!(f, "{b}"),
+            Value::List(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    item.fmt_repr(f, seen)?;
+                }
+                write!(f, "]")
+            }
+            Value::Map(map) => {
+                let map = map.borrow();
+                if map.is_empty() {
+                    return write!(f, "[:]");
+                }
+                write!(f, "[")?;
+                for (i, (key, value)) in map.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    let key = Value::from(key.clone());
+                    write!(f, "{}: ", key.repr())?;
+                    value.fmt_repr(f, seen)?;
+                }
+                write!(f, "]")
+            }
+            Value::Record(record) => write!(f, "<bentuk {}>", record.name),
+            Value::Instance(instance) => {
+                let instance = instance.borrow();
+                write!(f, "{}(", instance.record.name)?;
+                let fields = instance.record.fields.iter().zip(&instance.values);
+                for (i, (name, value)) in fields.enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{name}: ")?;
+                    value.fmt_repr(f, seen)?;
+                }
+                write!(f, ")")
+            }
+            Value::Constructor(variant) => {
+                write!(f, "<jenis {}.{}>", variant.enum_name, variant.name)
+            }
+            Value::Variant(v) => {
+                write!(f, "{}", v.variant.name)?;
+                if v.variant.fields.is_empty() {
+                    return Ok(());
+                }
+                write!(f, "(")?;
+                for (i, value) in v.values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    value.fmt_repr(f, seen)?;
+                }
+                write!(f, ")")
+            }
             Value::Function(func) => write!(f, "<function {}>", func.name),
             Value::NativeFunction { name, .. } => write!(f, "<native {name}>"),
             Value::None => write!(f, "none"),
@@ -64,14 +322,143 @@ impl fmt::Display for Value {
     }
 }
 
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_seen(f, &mut Vec::new())
+    }
+}
+
 impl Value {
+    pub fn list(items: Vec<Value>) -> Self {
+        Value::List(Rc::new(RefCell::new(items)))
+    }
+
+    /// the value as shown inside collections and messages, where strings
+    /// are quoted
+    pub fn repr(&self) -> String {
+        match self {
+            Value::Str(s) => format!("{s:?}"),
+            value => value.to_string(),
+        }
+    }
+
     pub fn is_truthy(&self) -> bool {
         match self {
             Value::Bool(b) => *b,
             Value::Int(i) => *i != 0,
+            Value::BigInt(i) => !i.is_zero(),
+            Value::Float(x) => *x != 0.0,
             Value::Str(s) => !s.is_empty(),
+            Value::List(items) => !items.borrow().is_empty(),
+            Value::Map(map) => !map.borrow().is_empty(),
             Value::None => false,
-            Value::Function(_) | Value::NativeFunction { .. } => true,
+            Value::Record(_)
+            | Value::Instance(_)
+            | Value::Constructor(_)
+            | Value::Variant(_)
+            | Value::Function(_)
+            | Value::NativeFunction { .. } => true,
+        }
+    }
+
+    /// the elements a `tiap` loop visits, `None` if the value is not a
+    /// collection
+    pub fn elements(&self) -> Option<Vec<Value>> {
+        match self {
+            Value::Str(s) => Some(s.chars().map(|c| Value::Str(c.to_string())).collect()),
+            Value::List(items) => Some(items.borrow().clone()),
+            Value::Map(map) => Some(map.borrow().keys().map(Value::from).collect()),
+            _ => None,
+        }
+    }
+
+    /// `self[index]`, where negative indices count from the end
+    fn index(&self, index: &Value, span: &Span) -> Result<Value> {
+        match self {
+            Value::List(items) => {
+                let items = items.borrow();
+                Ok(items[resolve_index(index, items.len(), span)?].clone())
+            }
+            Value::Map(map) => {
+                let key = MapKey::from_value(index, span)?;
+                match map.borrow().get(&key) {
+                    Some(value) => Ok(value.clone()),
+                    None => Err(error(
+                        ErrorKind::Index,
+                        span.clone(),
+                        format!("key {} not found in map", index.repr()),
+                    )),
+                }
+            }
+            Value::Str(s) => {
+                let i = resolve_index(index, s.chars().count(), span)?;
+                Ok(Value::Str(s.chars().nth(i).unwrap().to_string()))
+            }
+            _ => Err(error(
+                ErrorKind::Type,
+                span.clone(),
+                format!("cannot index into {}", self.type_name()),
+            )),
+        }
+    }
+
+    /// `self[start:end]`, out of range bounds are clamped
+    fn slice(&self, start: Option<i64>, end: Option<i64>, span: &Span) -> Result<Value> {
+        match self {
+            Value::List(items) => {
+                let items = items.borrow();
+                let range = slice_range(start, end, items.len());
+                Ok(Value::list(items[range].to_vec()))
+            }
+            Value::Str(s) => {
+                let range = slice_range(start, end, s.chars().count());
+                Ok(Value::Str(
+                    s.chars().skip(range.start).take(range.len()).collect(),
+                ))
+            }
+            _ => Err(error(
+                ErrorKind::Type,
+                span.clone(),
+                format!("cannot slice {}", self.type_name()),
+            )),
+        }
+    }
+
+    /// `self.name`, where methods come back bound to the instance as `gue`
+    fn field(&self, name: &str, span: &Span) -> Result<Value> {
+        let Value::Instance(instance) = self else {
+            return Err(error(
+                ErrorKind::Type,
+                span.clone(),
+                format!("{} has no fields", self.type_name()),
+            ));
+        };
+        let record = Rc::clone(&instance.borrow().record);
+        if let Some(i) = record.fields.iter().position(|f| f == name) {
+            return Ok(instance.borrow().values[i].clone());
+        }
+        let Some(method) = record.methods.get(name) else {
+            return Err(error(
+                ErrorKind::Name,
+                span.clone(),
+                format!("{} has no field {name}", record.name),
+            ));
+        };
+        let mut env = Environment::extend(Rc::clone(&method.closure));
+        env.define("gue".to_string(), self.clone());
+        Ok(Value::Function(Rc::new(FunctionValue {
+            closure: Rc::new(RefCell::new(env)),
+            ..FunctionValue::clone(method)
+        })))
+    }
+
+    /// the value of a number as a float
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Int(i) => Some(*i as f64),
+            Value::BigInt(i) => Some(i.to_f64()),
+            Value::Float(x) => Some(*x),
+            _ => None,
         }
     }
 
@@ -79,7 +466,15 @@ impl Value {
         match self {
             Value::Bool(_) => "bool",
             Value::Int(_) => "int",
+            Value::BigInt(_) => "big int",
+            Value::Float(_) => "float",
             Value::Str(_) => "string",
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
+            Value::Record(_) => "record_type",
+            Value::Instance(_) => "record",
+            Value::Constructor(_) => "constructor",
+            Value::Variant(_) => "variant",
             Value::Function(_) => "function",
             Value::NativeFunction { .. } => "native_function",
             Value::None => "none",
@@ -98,6 +493,7 @@ impl Environment {
         Self {
             parent: None,
             values: HashMap::new(),
+            constants: HashSet::new(),
         }
     }
 
@@ -106,6 +502,7 @@ impl Environment {
         Self {
             parent: Some(parent),
             values: HashMap::new(),
+            constants: HashSet::new(),
         }
     }
 
@@ -113,6 +510,16 @@ impl Environment {
         self.values.insert(name, value);
     }
 
+    pub fn define_const(&mut self, name: String, value: Value) {
+        self.constants.insert(name.clone());
+        self.values.insert(name, value);
+    }
+
+    /// whether `name` is a constant declared directly in this scope
+    pub fn is_constant(&self, name: &str) -> bool {
+        self.constants.contains(name)
+    }
+
     pub fn get(&self, name: &str) -> Option<Value> {
         if let Some(v) = self.values.get(name) {
             return Some(v.clone());
@@ -125,24 +532,483 @@ impl Environment {
         None
     }
 
-    pub fn assign(&mut self, name: &str, value: Value) -> bool {
+    /// the bindings defined directly in this scope, sorted by name
+    pub fn bindings(&self) -> Vec<(String, Value)> {
+        let mut bindings: Vec<_> = self
+            .values
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        bindings.sort_by(|(a, _), (b, _)| a.cmp(b));
+        bindings
+    }
+
+    /// overwrite the nearest binding of `name`
+    pub fn assign(&mut self, name: &str, value: Value) -> std::result::Result<(), AssignError> {
         if self.values.contains_key(name) {
+            if self.constants.contains(name) {
+                return Err(AssignError::Constant);
+            }
             self.values.insert(name.to_string(), value);
-            return true;
+            return Ok(());
         }
         if let Some(parent) = &self.parent {
             return parent.borrow_mut().assign(name, value);
         }
-        false
+        Err(AssignError::Undefined)
     }
 }
 
-fn native_print(args: &[Value]) -> Result<Value> {
+impl MapKey {
+    fn from_value(value: &Value, span: &Span) -> Result<Self> {
+        match value {
+            Value::Int(i) => Ok(MapKey::Int(*i)),
+            Value::Str(s) => Ok(MapKey::Str(s.clone())),
+            _ => Err(error(
+                ErrorKind::Type,
+                span.clone(),
+                format!("map keys must be int or string, got {}", value.type_name()),
+            )),
+        }
+    }
+}
+
+/// big ints that fit in an `i64` go back to being a `Value::Int`
+impl From<BigInt> for Value {
+    fn from(value: BigInt) -> Self {
+        match value.to_i64() {
+            Some(i) => Value::Int(i),
+            None => Value::BigInt(Rc::new(value)),
+        }
+    }
+}
+
+impl From<MapKey> for Value {
+    fn from(key: MapKey) -> Self {
+        match key {
+            MapKey::Int(i) => Value::Int(i),
+            MapKey::Str(s) => Value::Str(s),
+        }
+    }
+}
+
+impl Map {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &MapKey) -> Option<&Value> {
+        self.positions.get(key).map(|&i| &self.entries[i].1)
+    }
+
+    pub fn contains(&self, key: &MapKey) -> bool {
+        self.positions.contains_key(key)
+    }
+
+    /// insert or overwrite `key`, a new key goes after all existing ones
+    pub fn insert(&mut self, key: MapKey, value: Value) {
+        match self.positions.get(&key) {
+            Some(&i) => self.entries[i].1 = value,
+            None => {
+                self.positions.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &MapKey) -> Option<Value> {
+        let i = self.positions.remove(key)?;
+        let (_, value) = self.entries.remove(i);
+        for (key, _) in &self.entries[i..] {
+            *self.positions.get_mut(key).unwrap() -= 1;
+        }
+        Some(value)
+    }
+
+    /// entries in insertion order
+    pub fn iter(&self) -> impl Iterator<Item = (&MapKey, &Value)> {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = MapKey> + '_ {
+        self.entries.iter().map(|(key, _)| key.clone())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = Value> + '_ {
+        self.entries.iter().map(|(_, value)| value.clone())
+    }
+}
+
+// maps are equal when they have the same entries, in any order
+impl PartialEq for Map {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+/// resolve a possibly negative `index` into a collection of `len` elements
+fn resolve_index(index: &Value, len: usize, span: &Span) -> Result<usize> {
+    let Value::Int(i) = index else {
+        return Err(error(
+            ErrorKind::Type,
+            span.clone(),
+            format!("index must be int, got {}", index.type_name()),
+        ));
+    };
+    let resolved = if *i < 0 { *i + len as i64 } else { *i };
+    if resolved < 0 || resolved >= len as i64 {
+        return Err(error(
+            ErrorKind::Index,
+            span.clone(),
+            format!("index {i} out of bounds for length {len}"),
+        ));
+    }
+    Ok(resolved as usize)
+}
+
+/// the range of a slice over `len` elements, negative bounds count from the
+/// end and everything is clamped into `0..len`
+fn slice_range(start: Option<i64>, end: Option<i64>, len: usize) -> std::ops::Range<usize> {
+    let clamp = |i: i64| {
+        let i = if i < 0 { i + len as i64 } else { i };
+        i.clamp(0, len as i64) as usize
+    };
+    let start = start.map_or(0, clamp);
+    let end = end.map_or(len, clamp);
+    start..end.max(start)
+}
+
+/// the most decimal digits `pangkat` may build with big ints enabled
+const MAX_POW_DIGITS: usize = 100_000;
+
+fn big_int_op(op: &BinaryOp, a: &BigInt, b: &BigInt, span: Span) -> Result<Value> {
+    let result = match op {
+        BinaryOp::Add => a.add(b),
+        BinaryOp::Subtract => a.sub(b),
+        BinaryOp::Multiply => a.mul(b),
+        BinaryOp::Divide | BinaryOp::Modulo => match a.div_rem(b) {
+            Some((quotient, _)) if matches!(op, BinaryOp::Divide) => quotient,
+            Some((_, remainder)) => remainder,
+            None => return Err(error(ErrorKind::Arithmetic, span, "division by zero")),
+        },
+        BinaryOp::Power => match b.to_i64() {
+            Some(exp) if exp < 0 => return Ok(Value::Float(a.to_f64().powf(exp as f64))),
+            // estimate the size first, a huge power takes ages to compute
+            _ if a.pow_digits(b.to_f64()) > MAX_POW_DIGITS as f64 => {
+                return Err(error(
+                    ErrorKind::Limit,
+                    span,
+                    format!(
+                        "the result of pangkat would have about {:.0} digits",
+                        a.pow_digits(b.to_f64())
+                    ),
+                )
+                .with_note(format!(
+                    "pangkat can build ints of at most {MAX_POW_DIGITS} digits"
+                )));
+            }
+            Some(exp) if exp <= u32::MAX as i64 => a.pow(exp as u32),
+            _ => return Err(error(ErrorKind::Arithmetic, span, "integer overflow")),
+        },
+        // only reached when the int being shifted is big, the shift amount
+        // is already checked by `int_op`
+        BinaryOp::ShiftLeft | BinaryOp::ShiftRight => {
+            let Some(shift @ 0..64) = b.to_i64() else {
+                return Err(error(
+                    ErrorKind::Arithmetic,
+                    span,
+                    format!("invalid shift amount {b}"),
+                ));
+            };
+            let factor = BigInt::from(2).pow(shift as u32);
+            if matches!(op, BinaryOp::ShiftLeft) {
+                a.mul(&factor)
+            } else {
+                // shifting right rounds down, unlike division
+                let (quotient, remainder) = a.div_rem(&factor).unwrap();
+                if remainder < BigInt::from(0) {
+                    quotient.sub(&BigInt::from(1))
+                } else {
+                    quotient
+                }
+            }
+        }
+        _ => {
+            return Err(error(
+                ErrorKind::Arithmetic,
+                span,
+                format!("cannot apply {op} to ints outside of the 64 bit range"),
+            ));
+        }
+    };
+    Ok(Value::from(result))
+}
+
+/// arithmetic on floats, `None` for the bitwise operators
+fn float_op(op: &BinaryOp, a: f64, b: f64) -> Option<Value> {
+    Some(Value::Float(match op {
+        BinaryOp::Add => a + b,
+        BinaryOp::Subtract => a - b,
+        BinaryOp::Multiply => a * b,
+        BinaryOp::Divide => a / b,
+        BinaryOp::Modulo => a % b,
+        BinaryOp::Power => a.powf(b),
+        _ => return None,
+    }))
+}
+
+/// the order of two values, `None` when a float is NaN
+fn compare(lhs: &Value, rhs: &Value, span: &Span) -> Result<Option<Ordering>> {
+    compare_seen(lhs, rhs, span, &mut Vec::new())
+}
+
+/// `compare`, where `seen` holds the pairs of lists being compared further
+/// up, which are taken to be equal so cycles end
+fn compare_seen(
+    lhs: &Value,
+    rhs: &Value,
+    span: &Span,
+    seen: &mut Vec<(Ptr, Ptr)>,
+) -> Result<Option<Ordering>> {
+    Ok(match (lhs, rhs) {
+        (Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+        (Value::BigInt(a), Value::BigInt(b)) => Some(a.cmp(b)),
+        (Value::BigInt(a), Value::Int(b)) => Some(a.as_ref().cmp(&BigInt::from(*b))),
+        (Value::Int(a), Value::BigInt(b)) => Some(BigInt::from(*a).cmp(b)),
+        (Value::Str(a), Value::Str(b)) => Some(a.cmp(b)),
+        // element by element, a list that runs out first is smaller
+        (Value::List(a), Value::List(b)) => {
+            let pair = (Rc::as_ptr(a).cast(), Rc::as_ptr(b).cast());
+            if pair.0 == pair.1 || seen.contains(&pair) {
+                return Ok(Some(Ordering::Equal));
+            }
+            let (a, b) = (a.borrow(), b.borrow());
+            seen.push(pair);
+            for (x, y) in a.iter().zip(b.iter()) {
+                match compare_seen(x, y, span, seen) {
+                    Ok(Some(Ordering::Equal)) => {}
+                    result => {
+                        seen.pop();
+                        return result;
+                    }
+                }
+            }
+            seen.pop();
+            Some(a.len().cmp(&b.len()))
+        }
+        _ => match (lhs.as_f64(), rhs.as_f64()) {
+            (Some(a), Some(b)) => a.partial_cmp(&b),
+            _ => {
+                return Err(error(
+                    ErrorKind::Type,
+                    span.clone(),
+                    format!(
+                        "cannot compare {} with {}",
+                        lhs.type_name(),
+                        rhs.type_name()
+                    ),
+                ));
+            }
+        },
+    })
+}
+
+/// `n` fields, for arity errors of records and variants
+fn fields(n: usize) -> String {
+    match n {
+        1 => "1 field".to_owned(),
+        n => format!("{n} fields"),
+    }
+}
+
+/// the most bytes or elements `kali` may build by repeating a string or list
+const MAX_REPEAT_LEN: usize = 1 << 24;
+
+/// how many times `kali` repeats a string or list of `len` bytes or elements,
+/// refusing counts that are negative or build something too large
+fn repeat_count(count: i64, len: usize, span: &Span) -> Result<usize> {
+    let Ok(count) = usize::try_from(count) else {
+        return Err(error(
+            ErrorKind::Value,
+            span.clone(),
+            format!("cannot repeat a negative number of times ({count})"),
+        ));
+    };
+    match len.checked_mul(count) {
+        Some(total) if total <= MAX_REPEAT_LEN => Ok(count),
+        _ => Err(error(
+            ErrorKind::Value,
+            span.clone(),
+            format!("cannot repeat {count} times, the result would be too large"),
+        )
+        .with_note(format!(
+            "repeating can build at most {MAX_REPEAT_LEN} bytes or elements"
+        ))),
+    }
+}
+
+fn native_print(args: &[Value], _span: &Span) -> Result<Value> {
     let output: Vec<String> = args.iter().map(|v| v.to_string()).collect();
     println!("{}", output.join(" "));
     Ok(Value::None)
 }
 
+fn native_len(args: &[Value], span: &Span) -> Result<Value> {
+    match args {
+        [Value::Str(s)] => Ok(Value::Int(s.chars().count() as i64)),
+        [Value::List(items)] => Ok(Value::Int(items.borrow().len() as i64)),
+        [Value::Map(map)] => Ok(Value::Int(map.borrow().len() as i64)),
+        [other] => Err(error(
+            ErrorKind::Type,
+            span.clone(),
+            format!(
+                "len expects a string, list or map, got {}",
+                other.type_name()
+            ),
+        )),
+        _ => Err(error(
+            ErrorKind::Arity,
+            span.clone(),
+            format!("len expects 1 argument, got {}", args.len()),
+        )),
+    }
+}
+
+fn native_push(args: &[Value], span: &Span) -> Result<Value> {
+    match args {
+        [Value::List(items), value] => {
+            items.borrow_mut().push(value.clone());
+            Ok(Value::None)
+        }
+        [other, _] => Err(error(
+            ErrorKind::Type,
+            span.clone(),
+            format!("push expects a list, got {}", other.type_name()),
+        )),
+        _ => Err(error(
+            ErrorKind::Arity,
+            span.clone(),
+            format!("push expects 2 arguments, got {}", args.len()),
+        )),
+    }
+}
+
+fn native_keys(args: &[Value], span: &Span) -> Result<Value> {
+    let map = expect_map("keys", args, 1, span)?;
+    Ok(Value::list(map.borrow().keys().map(Value::from).collect()))
+}
+
+fn native_values(args: &[Value], span: &Span) -> Result<Value> {
+    let map = expect_map("values", args, 1, span)?;
+    Ok(Value::list(map.borrow().values().collect()))
+}
+
+fn native_has(args: &[Value], span: &Span) -> Result<Value> {
+    let map = expect_map("has", args, 2, span)?;
+    let key = MapKey::from_value(&args[1], span)?;
+    Ok(Value::Bool(map.borrow().contains(&key)))
+}
+
+/// remove a key from a map, returning its value or none when missing
+fn native_remove(args: &[Value], span: &Span) -> Result<Value> {
+    let map = expect_map("remove", args, 2, span)?;
+    let key = MapKey::from_value(&args[1], span)?;
+    Ok(map.borrow_mut().remove(&key).unwrap_or(Value::None))
+}
+
+/// `int(x)`, truncating floats and parsing strings
+fn native_int(args: &[Value], span: &Span) -> Result<Value> {
+    match args {
+        [Value::Int(_) | Value::BigInt(_)] => Ok(args[0].clone()),
+        [Value::Bool(b)] => Ok(Value::Int(*b as i64)),
+        // the cast saturates, so only floats inside the range are exact
+        [Value::Float(x)] if x.is_finite() && x.abs() < i64::MAX as f64 => {
+            Ok(Value::Int(*x as i64))
+        }
+        [Value::Str(s)] if s.trim().parse::<i64>().is_ok() => {
+            Ok(Value::Int(s.trim().parse().unwrap()))
+        }
+        [other] => Err(error(
+            ErrorKind::Value,
+            span.clone(),
+            format!("cannot convert {} to int", other.repr()),
+        )),
+        _ => Err(error(
+            ErrorKind::Arity,
+            span.clone(),
+            format!("int expects 1 argument, got {}", args.len()),
+        )),
+    }
+}
+
+/// `float(x)`, parsing strings
+fn native_float(args: &[Value], span: &Span) -> Result<Value> {
+    match args {
+        [Value::Int(i)] => Ok(Value::Float(*i as f64)),
+        [Value::BigInt(i)] => Ok(Value::Float(i.to_f64())),
+        [Value::Float(x)] => Ok(Value::Float(*x)),
+        [Value::Str(s)] if s.trim().parse::<f64>().is_ok() => {
+            Ok(Value::Float(s.trim().parse().unwrap()))
+        }
+        [other] => Err(error(
+            ErrorKind::Value,
+            span.clone(),
+            format!("cannot convert {} to float", other.repr()),
+        )),
+        _ => Err(error(
+            ErrorKind::Arity,
+            span.clone(),
+            format!("float expects 1 argument, got {}", args.len()),
+        )),
+    }
+}
+
+fn native_str(args: &[Value], span: &Span) -> Result<Value> {
+    match args {
+        [value] => Ok(Value::Str(value.to_string())),
+        _ => Err(error(
+            ErrorKind::Arity,
+            span.clone(),
+            format!("str expects 1 argument, got {}", args.len()),
+        )),
+    }
+}
+
+/// check that a map native got `arity` arguments, the first being a map
+fn expect_map<'a>(
+    name: &str,
+    args: &'a [Value],
+    arity: usize,
+    span: &Span,
+) -> Result<&'a Rc<RefCell<Map>>> {
+    if args.len() != arity {
+        return Err(error(
+            ErrorKind::Arity,
+            span.clone(),
+            format!("{name} expects {arity} arguments, got {}", args.len()),
+        ));
+    }
+    match &args[0] {
+        Value::Map(map) => Ok(map),
+        other => Err(error(
+            ErrorKind::Type,
+            span.clone(),
+            format!("{name} expects a map, got {}", other.type_name()),
+        )),
+    }
+}
+
 impl Default for Evaluator {
     fn default() -> Self {
         Self::new()
@@ -154,29 +1020,70 @@ impl Evaluator {
         let env = Environment::new();
         let evaluator = Self {
             env: Rc::new(RefCell::new(env)),
+            big_ints: false,
+            call_stack: Vec::new(),
         };
 
-        evaluator.env.borrow_mut().define(
-            "spill".to_string(),
+        evaluator.define_native("spill", native_print);
+        evaluator.define_native("len", native_len);
+        evaluator.define_native("push", native_push);
+        evaluator.define_native("keys", native_keys);
+        evaluator.define_native("values", native_values);
+        evaluator.define_native("has", native_has);
+        evaluator.define_native("remove", native_remove);
+        evaluator.define_native("int", native_int);
+        evaluator.define_native("float", native_float);
+        evaluator.define_native("str", native_str);
+
+        evaluator
+    }
+
+    fn define_native(&self, name: &str, func: NativeFn) {
+        self.env.borrow_mut().define(
+            name.to_string(),
             Value::NativeFunction {
-                name: "spill".to_string(),
-                func: native_print,
+                name: name.to_string(),
+                func,
             },
         );
+    }
 
-        evaluator
+    /// let ints grow past the `i64` range instead of failing on overflow
+    pub fn with_big_ints(mut self, enabled: bool) -> Self {
+        self.big_ints = enabled;
+        self
+    }
+
+    /// the current scope, which is the global scope between programs
+    pub fn env(&self) -> &Rc<RefCell<Environment>> {
+        &self.env
     }
 
     pub fn eval_program(&mut self, program: Program) -> Result<Value> {
-        for stmt in program.body {
-            self.eval_stmt(&stmt)?;
-        }
+        let result = (|| {
+            self.hoist_declarations(&program.body)?;
+            for stmt in &program.body {
+                self.eval_stmt(stmt)?;
+            }
 
-        if let Some(p) = &program.tail {
-            return self.eval_expr(p);
-        }
+            if let Some(p) = &program.tail {
+                return self.eval_expr(p);
+            }
 
-        Ok(Value::None)
+            Ok(Value::None)
+        })();
+
+        // the parser rejects jumps outside of loops and functions
+        match result {
+            Ok(value) | Err(Unwind::Return(value)) => Ok(value),
+            Err(Unwind::Error(err)) => Err(err),
+            Err(Unwind::Break(span)) => {
+                Err(error(ErrorKind::Syntax, span, "udahan outside of a loop"))
+            }
+            Err(Unwind::Continue(span)) => {
+                Err(error(ErrorKind::Syntax, span, "lanjut outside of a loop"))
+            }
+        }
     }
 
     fn eval_binary_op(
@@ -186,43 +1093,224 @@ impl Evaluator {
         rhs: Value,
         span: Span,
     ) -> Result<Value> {
-        match (op, lhs, rhs) {
-            (BinaryOp::Add, Value::Int(a), Value::Int(b)) => Ok(Value::Int(a + b)),
-            (BinaryOp::Subtract, Value::Int(a), Value::Int(b)) => Ok(Value::Int(a - b)),
-            (BinaryOp::Multiply, Value::Int(a), Value::Int(b)) => Ok(Value::Int(a * b)),
-            (BinaryOp::Divide, Value::Int(a), Value::Int(b)) => Ok(Value::Int(a / b)),
-            (BinaryOp::Or, Value::Bool(a), Value::Bool(b)) => Ok(Value::Bool(a || b)),
-            (BinaryOp::And, Value::Bool(a), Value::Bool(b)) => Ok(Value::Bool(a && b)),
-            (BinaryOp::Equal, Value::Int(a), Value::Int(b)) => Ok(Value::Bool(a == b)),
-            (BinaryOp::NotEqual, Value::Int(a), Value::Int(b)) => Ok(Value::Bool(a != b)),
-            (BinaryOp::LessThan, Value::Int(a), Value::Int(b)) => Ok(Value::Bool(a < b)),
-            (BinaryOp::LessOrEqual, Value::Int(a), Value::Int(b)) => Ok(Value::Bool(a <= b)),
-            (BinaryOp::GreaterThan, Value::Int(a), Value::Int(b)) => Ok(Value::Bool(a > b)),
-            (BinaryOp::GreaterOrEqual, Value::Int(a), Value::Int(b)) => Ok(Value::Bool(a >= b)),
-            _ => Err(error(span, "unsupported binary operation")),
-        }
-    }
-    fn eval_expr(&mut self, expr: &Expr) -> Result<Value> {
-        match expr {
-            Expr::Int(i) => Ok(Value::Int(i.value)),
-            Expr::Str(s) => Ok(Value::Str(s.value.clone())),
-            Expr::Binary(b) => {
-                let lhs = self.eval_expr(&b.lhs)?;
-                let rhs = self.eval_expr(&b.rhs)?;
-                self.eval_binary_op(&b.op, lhs, rhs, b.span.clone())
+        let ordering = match op {
+            BinaryOp::Equal => return Ok(Value::Bool(lhs == rhs)),
+            BinaryOp::NotEqual => return Ok(Value::Bool(lhs != rhs)),
+            BinaryOp::LessThan
+            | BinaryOp::LessOrEqual
+            | BinaryOp::GreaterThan
+            | BinaryOp::GreaterOrEqual => compare(&lhs, &rhs, &span)?,
+            _ => None,
+        };
+        match op {
+            BinaryOp::LessThan => return Ok(Value::Bool(ordering.is_some_and(|o| o.is_lt()))),
+            BinaryOp::LessOrEqual => return Ok(Value::Bool(ordering.is_some_and(|o| o.is_le()))),
+            BinaryOp::GreaterThan => return Ok(Value::Bool(ordering.is_some_and(|o| o.is_gt()))),
+            BinaryOp::GreaterOrEqual => {
+                return Ok(Value::Bool(ordering.is_some_and(|o| o.is_ge())));
+            }
+            _ => {}
+        }
+
+        let types = (lhs.type_name(), rhs.type_name());
+        let result = match (op, lhs, rhs) {
+            (BinaryOp::Add, Value::Str(a), Value::Str(b)) => Some(Value::Str(a + &b)),
+            (BinaryOp::Add, Value::List(a), Value::List(b)) => {
+                let mut items = a.borrow().clone();
+                items.extend(b.borrow().iter().cloned());
+                Some(Value::list(items))
+            }
+            (BinaryOp::Multiply, Value::Str(s), Value::Int(n))
+            | (BinaryOp::Multiply, Value::Int(n), Value::Str(s)) => {
+                Some(Value::Str(s.repeat(repeat_count(n, s.len(), &span)?)))
+            }
+            (BinaryOp::Multiply, Value::List(items), Value::Int(n))
+            | (BinaryOp::Multiply, Value::Int(n), Value::List(items)) => {
+                let items = items.borrow();
+                let count = repeat_count(n, items.len(), &span)?;
+                let len = items.len() * count;
+                Some(Value::list(
+                    items.iter().cycle().take(len).cloned().collect(),
+                ))
+            }
+            (op, Value::Int(a), Value::Int(b)) => Some(self.int_op(op, a, b, span.clone())?),
+            (op, Value::BigInt(a), Value::BigInt(b)) => Some(big_int_op(op, &a, &b, span.clone())?),
+            (op, Value::BigInt(a), Value::Int(b)) => {
+                Some(big_int_op(op, &a, &BigInt::from(b), span.clone())?)
+            }
+            (op, Value::Int(a), Value::BigInt(b)) => {
+                Some(big_int_op(op, &BigInt::from(a), &b, span.clone())?)
+            }
+            // an int meeting a float is widened to a float
+            (op, lhs, rhs) => match (lhs.as_f64(), rhs.as_f64()) {
+                (Some(a), Some(b)) => float_op(op, a, b),
+                _ => None,
+            },
+        };
+        result.ok_or_else(|| {
+            error(
+                ErrorKind::Type,
+                span,
+                format!("cannot apply {op} to {} and {}", types.0, types.1),
+            )
+        })
+    }
+
+    /// arithmetic on ints fails on overflow, or moves on to big ints when
+    /// they are enabled
+    fn int_op(&self, op: &BinaryOp, a: i64, b: i64, span: Span) -> Result<Value> {
+        let result = match op {
+            BinaryOp::Add => a.checked_add(b),
+            BinaryOp::Subtract => a.checked_sub(b),
+            BinaryOp::Multiply => a.checked_mul(b),
+            BinaryOp::Divide | BinaryOp::Modulo if b == 0 => {
+                return Err(error(ErrorKind::Arithmetic, span, "division by zero"));
+            }
+            BinaryOp::Divide => a.checked_div(b),
+            BinaryOp::Modulo => a.checked_rem(b),
+            // like dividing, a negative exponent makes a fraction
+            BinaryOp::Power if b < 0 => return Ok(Value::Float((a as f64).powf(b as f64))),
+            BinaryOp::Power => u32::try_from(b).ok().and_then(|exp| a.checked_pow(exp)),
+            BinaryOp::ShiftLeft | BinaryOp::ShiftRight if !(0..64).contains(&b) => {
+                return Err(error(
+                    ErrorKind::Arithmetic,
+                    span,
+                    format!("invalid shift amount {b}"),
+                ));
+            }
+            BinaryOp::ShiftLeft => i64::try_from((a as i128) << b).ok(),
+            BinaryOp::ShiftRight => Some(a >> b),
+            BinaryOp::BitAnd => Some(a & b),
+            BinaryOp::BitOr => Some(a | b),
+            BinaryOp::BitXor => Some(a ^ b),
+            // comparisons and `dan`/`atau` never reach here
+            _ => unreachable!("{op} is not arithmetic"),
+        };
+        match result {
+            Some(value) => Ok(Value::Int(value)),
+            None if self.big_ints => big_int_op(op, &BigInt::from(a), &BigInt::from(b), span),
+            None => Err(error(ErrorKind::Arithmetic, span, "integer overflow")),
+        }
+    }
+    fn eval_expr(&mut self, expr: &Expr) -> Flow<Value> {
+        match expr {
+            Expr::Int(i) => Ok(Value::Int(i.value)),
+            Expr::BigInt(i) if self.big_ints => Ok(Value::BigInt(Rc::new(i.value.clone()))),
+            Expr::BigInt(i) => Err(error(
+                ErrorKind::Value,
+                i.span.clone(),
+                "integer literal is too large",
+            )
+            .with_note("run with --big-ints to allow ints past 64 bits")
+            .into()),
+            Expr::Float(x) => Ok(Value::Float(x.value)),
+            Expr::Bool(b) => Ok(Value::Bool(b.value)),
+            Expr::None(_) => Ok(Value::None),
+            Expr::Str(s) => Ok(Value::Str(s.value.clone())),
+            Expr::Template(t) => {
+                let mut value = String::new();
+                for part in &t.parts {
+                    match part {
+                        TemplatePart::Str(s) => value.push_str(s),
+                        TemplatePart::Expr(e) => value.push_str(&self.eval_expr(e)?.to_string()),
+                    }
+                }
+                Ok(Value::Str(value))
+            }
+            // `dan` and `atau` give back the operand that decided the result,
+            // leaving the right one unevaluated when the left one decides
+            Expr::Binary(b) if matches!(b.op, BinaryOp::And | BinaryOp::Or) => {
+                let lhs = self.eval_expr(&b.lhs)?;
+                if lhs.is_truthy() == matches!(b.op, BinaryOp::Or) {
+                    return Ok(lhs);
+                }
+                self.eval_expr(&b.rhs)
+            }
+            Expr::Binary(b) => {
+                let lhs = self.eval_expr(&b.lhs)?;
+                let rhs = self.eval_expr(&b.rhs)?;
+                Ok(self.eval_binary_op(&b.op, lhs, rhs, b.span.clone())?)
             }
             Expr::Block(b) => self.eval_block(b),
+            Expr::List(l) => {
+                let items = l
+                    .items
+                    .iter()
+                    .map(|item| self.eval_expr(item))
+                    .collect::<Flow<Vec<Value>>>()?;
+                Ok(Value::list(items))
+            }
+            Expr::Map(m) => {
+                let mut map = Map::new();
+                for entry in &m.entries {
+                    let key = self.eval_expr(&entry.key)?;
+                    let key = MapKey::from_value(&key, &entry.span)?;
+                    let value = self.eval_expr(&entry.value)?;
+                    map.insert(key, value);
+                }
+                Ok(Value::Map(Rc::new(RefCell::new(map))))
+            }
+            Expr::Index(i) => {
+                let target = self.eval_expr(&i.target)?;
+                let index = self.eval_expr(&i.index)?;
+                Ok(target.index(&index, &i.span)?)
+            }
+            Expr::Field(f) => {
+                let target = self.eval_expr(&f.target)?;
+                Ok(target.field(&f.name, &f.span)?)
+            }
+            Expr::Slice(sl) => {
+                let target = self.eval_expr(&sl.target)?;
+                let mut bounds = [None, None];
+                for (bound, expr) in bounds.iter_mut().zip([&sl.start, &sl.end]) {
+                    let Some(expr) = expr else {
+                        continue;
+                    };
+                    match self.eval_expr(expr)? {
+                        Value::Int(i) => *bound = Some(i),
+                        other => {
+                            return Err(error(
+                                ErrorKind::Type,
+                                sl.span.clone(),
+                                format!("slice bounds must be int, got {}", other.type_name()),
+                            )
+                            .into());
+                        }
+                    }
+                }
+                Ok(target.slice(bounds[0], bounds[1], &sl.span)?)
+            }
+            Expr::Lambda(l) => Ok(Value::Function(Rc::new(FunctionValue {
+                name: "lambda".to_string(),
+                params: l.params.clone(),
+                body: l.body.clone(),
+                closure: Rc::clone(&self.env),
+            }))),
             Expr::Unary(u) => {
                 let val = self.eval_expr(&u.rhs)?;
 
                 match (&u.op, &val) {
-                    (UnaryOp::Minus, Value::Int(i)) => Ok(Value::Int(-i)),
+                    (UnaryOp::Minus, Value::Int(i)) => match i.checked_neg() {
+                        Some(i) => Ok(Value::Int(i)),
+                        None if self.big_ints => Ok(Value::from(BigInt::from(*i).neg())),
+                        None => {
+                            Err(
+                                error(ErrorKind::Arithmetic, u.span.clone(), "integer overflow")
+                                    .into(),
+                            )
+                        }
+                    },
+                    (UnaryOp::Minus, Value::BigInt(i)) => Ok(Value::from(i.neg())),
+                    (UnaryOp::BitNot, Value::Int(i)) => Ok(Value::Int(!i)),
+                    (UnaryOp::Minus, Value::Float(x)) => Ok(Value::Float(-x)),
                     (UnaryOp::Not, Value::Bool(b)) => Ok(Value::Bool(!b)),
                     (UnaryOp::Not, _) => Ok(Value::Bool(!val.is_truthy())),
                     _ => Err(error(
-                        Span::empty(),
+                        ErrorKind::Type,
+                        u.span.clone(),
                         format!("cannot apply {:?} to {}", u.op, val.type_name()),
-                    )),
+                    )
+                    .into()),
                 }
             }
             Expr::If(expr_if) => {
@@ -237,12 +1325,41 @@ impl Evaluator {
                 }
                 Ok(Value::None)
             }
+            Expr::Match(m) => {
+                let value = self.eval_expr(&m.value)?;
+                for arm in &m.arms {
+                    let mut env = Environment::extend(Rc::clone(&self.env));
+                    if !self.match_pattern(&arm.pattern, &value, &mut env)? {
+                        continue;
+                    }
+                    let result = self.with_env(Rc::new(RefCell::new(env)), |this| {
+                        if let Some(guard) = &arm.guard
+                            && !this.eval_expr(guard)?.is_truthy()
+                        {
+                            return Ok(None);
+                        }
+                        this.eval_expr(&arm.body).map(Some)
+                    })?;
+                    if let Some(result) = result {
+                        return Ok(result);
+                    }
+                }
+                Err(error(
+                    ErrorKind::Match,
+                    m.span.clone(),
+                    format!("non-exhaustive cocokin: no arm matches {}", value.repr()),
+                )
+                .with_note("add a `_ => ...` arm to handle every other value")
+                .into())
+            }
             Expr::Identifier(ident) => match self.env.borrow().get(&ident.name) {
                 Some(v) => Ok(v),
                 None => Err(error(
-                    Span::empty(),
+                    ErrorKind::Name,
+                    ident.span.clone(),
                     format!("undefined variable: {}", ident.name),
-                )),
+                )
+                .into()),
             },
             Expr::Call(call) => {
                 let callee = self.eval_expr(&call.callee)?;
@@ -251,91 +1368,459 @@ impl Evaluator {
                     .args
                     .iter()
                     .map(|a| self.eval_expr(a))
-                    .collect::<Result<Vec<Value>>>()?;
+                    .collect::<Flow<Vec<Value>>>()?;
 
-                self.call_function(callee, args)
+                self.call_function(callee, args, &call.span)
             }
-
-            _ => Err(error(Span::empty(), "invalid expression".to_string())),
         }
     }
-    fn call_function(&mut self, callee: Value, args: Vec<Value>) -> Result<Value> {
+    /// call `callee`, errors about the call itself point at `span`
+    fn call_function(&mut self, callee: Value, args: Vec<Value>, span: &Span) -> Flow<Value> {
         match callee {
             Value::Function(func) => {
                 if args.len() != func.params.len() {
                     return Err(error(
-                        Span::empty(),
+                        ErrorKind::Arity,
+                        span.clone(),
                         format!(
                             "expected {} arguments, but got {}",
                             func.params.len(),
                             args.len()
                         ),
-                    ));
+                    )
+                    .into());
                 }
 
-                let mut func_env = Environment::extend(Rc::new(RefCell::new(func.closure.clone())));
+                if self.call_stack.len() >= MAX_CALL_DEPTH {
+                    return Err(error(
+                        ErrorKind::Limit,
+                        span.clone(),
+                        format!("maximum call depth of {MAX_CALL_DEPTH} exceeded"),
+                    )
+                    .with_note("a function probably calls itself without stopping")
+                    .into());
+                }
+
+                let mut func_env = Environment::extend(Rc::clone(&func.closure));
 
                 for (param, arg) in func.params.iter().zip(args) {
                     func_env.define(param.clone(), arg);
                 }
 
-                let outer_env = Rc::clone(&self.env);
-                self.env = Rc::new(RefCell::new(func_env));
-
-                let result = self.eval_block(&func.body)?;
-
-                self.env = outer_env;
-
-                Ok(result)
+                self.call_stack.push(Frame {
+                    function: func.name.clone(),
+                    span: span.clone(),
+                });
+                // each jaksel call takes a few native frames, grow the stack
+                // on the heap so the depth limit is hit before it runs out
+                let result = stacker::maybe_grow(256 * 1024, 4 * 1024 * 1024, || {
+                    self.with_env(Rc::new(RefCell::new(func_env)), |this| {
+                        this.eval_block(&func.body)
+                    })
+                });
+                let result = match result {
+                    Err(Unwind::Return(value)) => Ok(value),
+                    // the innermost call sees the whole stack, outer calls
+                    // leave its backtrace alone
+                    Err(Unwind::Error(err)) if err.backtrace().is_empty() => {
+                        let backtrace = self.call_stack.iter().rev().cloned().collect();
+                        Err(err.with_backtrace(backtrace).into())
+                    }
+                    result => result,
+                };
+                self.call_stack.pop();
+                result
+            }
+            Value::NativeFunction { func, .. } => Ok(func(&args, span)?),
+            Value::Record(record) => {
+                if args.len() != record.fields.len() {
+                    return Err(error(
+                        ErrorKind::Arity,
+                        span.clone(),
+                        format!(
+                            "{} expects {}, but got {}",
+                            record.name,
+                            fields(record.fields.len()),
+                            args.len()
+                        ),
+                    )
+                    .into());
+                }
+                Ok(Value::Instance(Rc::new(RefCell::new(Instance {
+                    record,
+                    values: args,
+                }))))
+            }
+            Value::Constructor(variant) => {
+                if args.len() != variant.fields.len() {
+                    return Err(error(
+                        ErrorKind::Arity,
+                        span.clone(),
+                        format!(
+                            "{} expects {}, but got {}",
+                            variant.name,
+                            fields(variant.fields.len()),
+                            args.len()
+                        ),
+                    )
+                    .into());
+                }
+                Ok(Value::Variant(Rc::new(Variant {
+                    variant,
+                    values: args,
+                })))
             }
-            Value::NativeFunction { func, .. } => func(&args),
             _ => Err(error(
-                Span::empty(),
+                ErrorKind::Type,
+                span.clone(),
                 format!("{} is not callable", callee.type_name()),
-            )),
+            )
+            .into()),
         }
     }
-    fn eval_block(&mut self, block: &Block) -> Result<Value> {
-        let outer_env = Rc::clone(&self.env);
-        let inner_env = Environment::extend(Rc::clone(&outer_env));
-        self.env = Rc::new(RefCell::new(inner_env));
-
-        for b in &block.body {
-            self.eval_stmt(b)?;
+    /// whether `value` matches `pattern`, defining the names the pattern
+    /// binds in `env`
+    fn match_pattern(
+        &mut self,
+        pattern: &Pattern,
+        value: &Value,
+        env: &mut Environment,
+    ) -> Flow<bool> {
+        match pattern {
+            Pattern::Wildcard => Ok(true),
+            Pattern::Binding(name) => {
+                env.define(name.clone(), value.clone());
+                Ok(true)
+            }
+            Pattern::Literal(e) => Ok(self.eval_expr(e)? == *value),
+            Pattern::Variant(p) => {
+                let variant = match self.env.borrow().get(&p.name) {
+                    Some(Value::Constructor(variant)) => variant,
+                    Some(Value::Variant(v)) => Rc::clone(&v.variant),
+                    _ => {
+                        return Err(error(
+                            ErrorKind::Type,
+                            p.span.clone(),
+                            format!("{} is not a variant", p.name),
+                        )
+                        .into());
+                    }
+                };
+                let Value::Variant(v) = value else {
+                    return Ok(false);
+                };
+                if !Rc::ptr_eq(&v.variant, &variant) {
+                    return Ok(false);
+                }
+                let Some(fields) = &p.fields else {
+                    return Ok(true);
+                };
+                if fields.len() != v.values.len() {
+                    return Err(error(
+                        ErrorKind::Arity,
+                        p.span.clone(),
+                        format!(
+                            "{} has {}, but the pattern has {}",
+                            p.name,
+                            self::fields(v.values.len()),
+                            fields.len()
+                        ),
+                    )
+                    .into());
+                }
+                for (field, value) in fields.iter().zip(&v.values) {
+                    if !self.match_pattern(field, value, env)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
         }
+    }
+    fn eval_block(&mut self, block: &Block) -> Flow<Value> {
+        let inner_env = Environment::extend(Rc::clone(&self.env));
+        self.with_env(Rc::new(RefCell::new(inner_env)), |this| {
+            this.hoist_declarations(&block.body)?;
+            for b in &block.body {
+                this.eval_stmt(b)?;
+            }
 
-        let result = if let Some(tail) = &block.tail {
-            self.eval_expr(tail)?
-        } else {
-            Value::None
-        };
-
+            if let Some(tail) = &block.tail {
+                this.eval_expr(tail)
+            } else {
+                Ok(Value::None)
+            }
+        })
+    }
+    /// define the functions and records declared in `body` before running
+    /// any of it, so they can be used regardless of order
+    fn hoist_declarations(&mut self, body: &[Stmt]) -> Flow<()> {
+        for stmt in body {
+            let (name, span, value) = match stmt {
+                Stmt::Fn(f) => {
+                    let function = Value::Function(Rc::new(FunctionValue {
+                        name: f.name.clone(),
+                        params: f.params.clone(),
+                        body: f.body.clone(),
+                        closure: Rc::clone(&self.env),
+                    }));
+                    (&f.name, &f.span, function)
+                }
+                Stmt::Record(r) => (&r.name, &r.span, self.record_type(r)),
+                _ => continue,
+            };
+            if self.env.borrow().is_constant(name) {
+                return Err(error(
+                    ErrorKind::Constant,
+                    span.clone(),
+                    format!("cannot redeclare constant {name}"),
+                )
+                .into());
+            }
+            self.env.borrow_mut().define(name.clone(), value);
+        }
+        Ok(())
+    }
+    /// the type declared by `r`, its methods close over the current scope
+    fn record_type(&self, r: &StmtRecord) -> Value {
+        let methods = r
+            .methods
+            .iter()
+            .map(|m| {
+                let method = Rc::new(FunctionValue {
+                    name: format!("{}.{}", r.name, m.name),
+                    params: m.params.clone(),
+                    body: m.body.clone(),
+                    closure: Rc::clone(&self.env),
+                });
+                (m.name.clone(), method)
+            })
+            .collect();
+        Value::Record(Rc::new(RecordType {
+            name: r.name.clone(),
+            fields: r.fields.clone(),
+            methods,
+        }))
+    }
+    /// run one iteration of a loop, returning whether the loop goes on
+    fn eval_loop_body(&mut self, body: &Block) -> Flow<bool> {
+        match self.eval_block(body) {
+            Ok(_) | Err(Unwind::Continue(_)) => Ok(true),
+            Err(Unwind::Break(_)) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+    /// run one iteration of a `tiap` loop with a fresh binding for `value`,
+    /// so closures created in the body capture this iteration's value
+    fn eval_for_iteration(&mut self, f: &StmtFor, value: Value) -> Flow<bool> {
+        let mut env = Environment::extend(Rc::clone(&self.env));
+        env.define(f.name.clone(), value);
+        self.with_env(Rc::new(RefCell::new(env)), |this| {
+            this.eval_loop_body(&f.body)
+        })
+    }
+    /// run `f` with `env` as the current scope, restoring the previous scope
+    /// afterwards even if `f` fails
+    fn with_env<T>(
+        &mut self,
+        env: Rc<RefCell<Environment>>,
+        f: impl FnOnce(&mut Self) -> Flow<T>,
+    ) -> Flow<T> {
+        let outer_env = std::mem::replace(&mut self.env, env);
+        let result = f(self);
         self.env = outer_env;
-
-        Ok(result)
+        result
     }
-    fn eval_stmt(&mut self, stmt: &Stmt) -> Result<()> {
+    fn eval_stmt(&mut self, stmt: &Stmt) -> Flow<()> {
         match stmt {
-            Stmt::Fn(f) => {
-                let function = Value::Function(Rc::new(FunctionValue {
-                    name: f.name.clone(),
-                    params: f.params.clone(),
-                    body: f.body.clone(),
-                    closure: self.env.borrow().clone(),
-                }));
-                self.env.borrow_mut().assign(&f.name, function);
+            // defined up front by `hoist_declarations`
+            Stmt::Fn(_) | Stmt::Record(_) => Ok(()),
+            Stmt::Enum(e) => {
+                for v in &e.variants {
+                    if self.env.borrow().is_constant(&v.name) {
+                        return Err(error(
+                            ErrorKind::Constant,
+                            e.span.clone(),
+                            format!("cannot redeclare constant {}", v.name),
+                        )
+                        .into());
+                    }
+                    let variant = Rc::new(VariantType {
+                        enum_name: e.name.clone(),
+                        name: v.name.clone(),
+                        fields: v.fields.clone(),
+                    });
+                    // variants without fields are values, the others build one
+                    let value = if variant.fields.is_empty() {
+                        Value::Variant(Rc::new(Variant {
+                            variant,
+                            values: Vec::new(),
+                        }))
+                    } else {
+                        Value::Constructor(variant)
+                    };
+                    self.env.borrow_mut().define(v.name.clone(), value);
+                }
                 Ok(())
             }
             Stmt::Let(l) => {
                 let val = self.eval_expr(&l.value)?;
-                self.env.borrow_mut().define(l.name.clone(), val);
+                let mut env = self.env.borrow_mut();
+                if env.is_constant(&l.name) {
+                    return Err(error(
+                        ErrorKind::Constant,
+                        l.span.clone(),
+                        format!("cannot redeclare constant {}", l.name),
+                    )
+                    .into());
+                }
+                if l.constant {
+                    env.define_const(l.name.clone(), val);
+                } else {
+                    env.define(l.name.clone(), val);
+                }
                 Ok(())
             }
+            Stmt::Assign(a) => {
+                let val = self.eval_expr(&a.value)?;
+                if let Expr::Index(i) = &a.target {
+                    let target = self.eval_expr(&i.target)?;
+                    let index = self.eval_expr(&i.index)?;
+                    match target {
+                        Value::List(items) => {
+                            let mut items = items.borrow_mut();
+                            let index = resolve_index(&index, items.len(), &i.span)?;
+                            items[index] = val;
+                        }
+                        Value::Map(map) => {
+                            let key = MapKey::from_value(&index, &i.span)?;
+                            map.borrow_mut().insert(key, val);
+                        }
+                        _ => {
+                            return Err(error(
+                                ErrorKind::Type,
+                                a.span.clone(),
+                                format!("cannot assign into {}", target.type_name()),
+                            )
+                            .into());
+                        }
+                    }
+                    return Ok(());
+                }
+                if let Expr::Field(f) = &a.target {
+                    let target = self.eval_expr(&f.target)?;
+                    let Value::Instance(instance) = &target else {
+                        return Err(error(
+                            ErrorKind::Type,
+                            f.span.clone(),
+                            format!("{} has no fields", target.type_name()),
+                        )
+                        .into());
+                    };
+                    let mut instance = instance.borrow_mut();
+                    let Some(i) = instance.record.fields.iter().position(|n| *n == f.name) else {
+                        return Err(error(
+                            ErrorKind::Name,
+                            f.span.clone(),
+                            format!("{} has no field {}", instance.record.name, f.name),
+                        )
+                        .into());
+                    };
+                    instance.values[i] = val;
+                    return Ok(());
+                }
+                let Expr::Identifier(ident) = &a.target else {
+                    return Err(error(
+                        ErrorKind::Syntax,
+                        a.span.clone(),
+                        "invalid assignment target",
+                    )
+                    .into());
+                };
+                match self.env.borrow_mut().assign(&ident.name, val) {
+                    Ok(()) => Ok(()),
+                    Err(AssignError::Undefined) => Err(error(
+                        ErrorKind::Name,
+                        a.span.clone(),
+                        format!("cannot assign to undefined variable {}", ident.name),
+                    )
+                    .into()),
+                    Err(AssignError::Constant) => Err(error(
+                        ErrorKind::Constant,
+                        a.span.clone(),
+                        format!("cannot assign to constant {}", ident.name),
+                    )
+                    .into()),
+                }
+            }
+            Stmt::While(w) => {
+                while self.eval_expr(&w.cond)?.is_truthy() {
+                    if !self.eval_loop_body(&w.body)? {
+                        break;
+                    }
+                }
+                Ok(())
+            }
+            Stmt::For(f) => {
+                let iter = self.eval_expr(&f.iter)?;
+                if let Some(end) = &f.end {
+                    let end = self.eval_expr(end)?;
+                    let (Value::Int(start), Value::Int(end)) = (&iter, &end) else {
+                        return Err(error(
+                            ErrorKind::Type,
+                            f.span.clone(),
+                            format!(
+                                "range bounds must be int, got {} and {}",
+                                iter.type_name(),
+                                end.type_name()
+                            ),
+                        )
+                        .into());
+                    };
+                    for i in *start..*end {
+                        if !self.eval_for_iteration(f, Value::Int(i))? {
+                            break;
+                        }
+                    }
+                    return Ok(());
+                }
+
+                let Some(elements) = iter.elements() else {
+                    return Err(error(
+                        ErrorKind::Type,
+                        f.span.clone(),
+                        format!("cannot iterate over {}", iter.type_name()),
+                    )
+                    .into());
+                };
+                for element in elements {
+                    if !self.eval_for_iteration(f, element)? {
+                        break;
+                    }
+                }
+                Ok(())
+            }
+            Stmt::Return(r) => {
+                let value = match &r.value {
+                    Some(value) => self.eval_expr(value)?,
+                    None => Value::None,
+                };
+                Err(Unwind::Return(value))
+            }
+            Stmt::Break(b) => Err(Unwind::Break(b.span.clone())),
+            Stmt::Continue(c) => Err(Unwind::Continue(c.span.clone())),
+            // `parse` rejects programs with syntax errors, so this only runs
+            // when a program is evaluated despite them
+            Stmt::Error(e) => Err(error(
+                ErrorKind::Syntax,
+                e.span.clone(),
+                "cannot run a statement with a syntax error",
+            )
+            .into()),
             Stmt::Expr(e) => {
                 self.eval_expr(e)?; // do nothing and look for potential errors
                 Ok(())
             }
-            _ => panic!("not supported"),
         }
     }
 }
@@ -348,7 +1833,10 @@ mod tests {
 
     fn eval(source: &str) -> Result<Value> {
         let mut cursor = Cursor::new(source, lex(source));
-        let program = parse_program(&mut cursor)?;
+        let program = parse_program(&mut cursor);
+        if let Some(err) = cursor.errors.into_iter().next() {
+            return Err(err);
+        }
         dbg!(&program);
         let mut evaluator = Evaluator::new();
         evaluator.eval_program(program)
@@ -374,6 +1862,21 @@ mod tests {
         assert_eq!(eval(r#"spill("x", 1 tambah 2, "y")"#).unwrap(), Value::None);
     }
 
+    #[test]
+    fn test_fn_declaration() {
+        assert_eq!(
+            eval("so about f(a) { a tambah 1 } f(1)").unwrap(),
+            Value::Int(2)
+        );
+    }
+
+    #[test]
+    fn test_unary() {
+        assert_eq!(eval("-5").unwrap(), Value::Int(-5));
+        assert_eq!(eval("--5").unwrap(), Value::Int(5));
+        assert_eq!(eval("bukan 0").unwrap(), Value::Bool(true));
+    }
+
     #[test]
     fn test_print_no_args() {
         assert_eq!(eval("spill()").unwrap(), Value::None);
@@ -383,4 +1886,1149 @@ mod tests {
     fn test_print_expression() {
         assert_eq!(eval("spill(1 tambah 2)").unwrap(), Value::None);
     }
+
+    #[test]
+    fn test_env_restored_after_error() {
+        let mut evaluator = Evaluator::new();
+        let source = "literally x itu 1 wkwk { literally y itu 2 wkwk gak_ada }";
+        let program = parse_program(&mut Cursor::new(source, lex(source)));
+        assert!(evaluator.eval_program(program).is_err());
+        assert!(evaluator.env().borrow().get("y").is_none());
+        assert_eq!(evaluator.env().borrow().get("x"), Some(Value::Int(1)));
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        assert_eq!(
+            eval(r#""a\"b\\c\td\n\u{41}\u{1F600}""#).unwrap(),
+            Value::Str("a\"b\\c\td\nA\u{1F600}".to_string())
+        );
+    }
+
+    #[test]
+    fn test_raw_strings() {
+        assert_eq!(eval(r#"r"a\nb""#).unwrap(), Value::Str(r"a\nb".to_string()));
+        assert_eq!(
+            eval(r##"r#"say "hi""#"##).unwrap(),
+            Value::Str(r#"say "hi""#.to_string())
+        );
+    }
+
+    #[test]
+    fn test_multiline_strings() {
+        let source = "\"\"\"\nSELECT *\n  FROM \"orang\"\\t\n\"\"\"";
+        assert_eq!(
+            eval(source).unwrap(),
+            Value::Str("SELECT *\n  FROM \"orang\"\t\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_invalid_escape() {
+        let err = eval(r#"literally x itu "ab\qc" wkwk"#).unwrap_err();
+        assert_eq!(err.span(), Span { start: 19, end: 21 });
+
+        let err = eval(r#""\u{110000}""#).unwrap_err();
+        assert_eq!(err.span(), Span { start: 1, end: 11 });
+    }
+
+    #[test]
+    fn test_string_interpolation() {
+        assert_eq!(
+            eval(
+                r#"literally umur itu 22 wkwk "umur lu {umur} tahun, {umur tambah 1} tahun depan""#
+            )
+            .unwrap(),
+            Value::Str("umur lu 22 tahun, 23 tahun depan".to_string())
+        );
+        assert_eq!(
+            eval(r#""{"nested {1}"}!" "#).unwrap(),
+            Value::Str("nested 1!".to_string())
+        );
+        assert_eq!(
+            eval(r#""\{literal\}" "#).unwrap(),
+            Value::Str("{literal}".to_string())
+        );
+    }
+
+    #[test]
+    fn test_string_interpolation_errors() {
+        let err = eval(r#""a {1 tambah} b""#).unwrap_err();
+        assert_eq!(err.span(), Span { start: 12, end: 12 });
+
+        let err = eval(r#""a {1 2} b""#).unwrap_err();
+        assert_eq!(err.span(), Span { start: 6, end: 7 });
+
+        let err = eval(r#""a {} b""#).unwrap_err();
+        assert_eq!(err.span(), Span { start: 3, end: 5 });
+
+        // every unescaped `{` opens a placeholder, an unclosed one is
+        // reported at the brace instead of running on to the end of the source
+        let err = eval("spill(\"{\") wkwk\nspill(\"}\") wkwk").unwrap_err();
+        assert_eq!(err.message(), "unclosed `{` in string");
+        assert_eq!(err.span(), Span { start: 7, end: 8 });
+        assert_eq!(
+            eval(r#""\{\"a\": {1 tambah 1}}""#).unwrap(),
+            Value::Str(r#"{"a": 2}"#.to_string())
+        );
+    }
+
+    #[test]
+    fn test_reassign() {
+        assert_eq!(
+            eval("literally x itu 1 wkwk x itu x tambah 1 wkwk x").unwrap(),
+            Value::Int(2)
+        );
+        // assigns the nearest enclosing binding instead of declaring a new one
+        assert_eq!(
+            eval("literally x itu 1 wkwk { x itu 5 wkwk } wkwk x").unwrap(),
+            Value::Int(5)
+        );
+        assert_eq!(
+            eval("literally x itu 1 wkwk { literally x itu 2 wkwk x itu 5 wkwk } wkwk x").unwrap(),
+            Value::Int(1)
+        );
+    }
+
+    #[test]
+    fn test_assign_undefined() {
+        let err = eval("y itu 1 wkwk").unwrap_err();
+        assert_eq!(err.message(), "cannot assign to undefined variable y");
+        assert_eq!(err.span(), Span { start: 0, end: 1 });
+    }
+
+    #[test]
+    fn test_runtime_error_spans() {
+        let err = eval("spill(1, x)").unwrap_err();
+        assert_eq!(err.code(), "JKS0003");
+        assert_eq!(err.span(), Span { start: 9, end: 10 });
+
+        let err = eval("so about f(a) { a } f(1, 2)").unwrap_err();
+        assert_eq!(err.code(), "JKS0006");
+        assert_eq!(err.span(), Span { start: 20, end: 27 });
+
+        let err = eval("literally x itu 1 wkwk x(2)").unwrap_err();
+        assert_eq!(err.code(), "JKS0005");
+        assert_eq!(err.span(), Span { start: 23, end: 27 });
+
+        let err = eval(r#"1 tambah -"a""#).unwrap_err();
+        assert_eq!(err.code(), "JKS0005");
+        assert_eq!(err.span(), Span { start: 9, end: 10 });
+
+        let err = eval("len(1)").unwrap_err();
+        assert_eq!(err.code(), "JKS0005");
+        assert_eq!(err.span(), Span { start: 0, end: 6 });
+
+        let err = eval("has([:], [1])").unwrap_err();
+        assert_eq!(err.code(), "JKS0005");
+        assert_eq!(err.span(), Span { start: 0, end: 13 });
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let code = |source: &str| {
+            crate::parse(source)
+                .and_then(|program| Evaluator::new().eval_program(program))
+                .unwrap_err()
+                .code()
+        };
+        assert_eq!(code(r#""abc"#), "JKS0001");
+        assert_eq!(code("99999999999999999999"), "JKS0009");
+        assert_eq!(code("@"), "JKS0001");
+        assert_eq!(code("spill(1"), "JKS0002");
+        assert_eq!(code("udahan wkwk"), "JKS0002");
+        assert_eq!(code("x itu 1 wkwk"), "JKS0003");
+        assert_eq!(code("bentuk T { a } T(1).b"), "JKS0003");
+        assert_eq!(code("seriously x itu 1 wkwk x itu 2 wkwk"), "JKS0004");
+        assert_eq!(code(r#"1 lebih kecil "a""#), "JKS0005");
+        assert_eq!(code("tiap i dari 1 { }"), "JKS0005");
+        assert_eq!(code("len()"), "JKS0006");
+        assert_eq!(code("1 bagi 0"), "JKS0007");
+        assert_eq!(code("[1][1]"), "JKS0008");
+        assert_eq!(code(r#"[:]["a"]"#), "JKS0008");
+        assert_eq!(code(r#"float("x")"#), "JKS0009");
+        assert_eq!(code("cocokin 1 { 2 => 3 }"), "JKS0010");
+        assert_eq!(code("so about f() { f() } f()"), "JKS0011");
+    }
+
+    #[test]
+    fn test_backtrace() {
+        let source = "
+            so about tambahin(a, b) { a tambah b }
+            so about hitung(x) { tambahin(x, \"1\") }
+            hitung(1)";
+        let err = eval(source).unwrap_err();
+        let frames: Vec<(&str, usize)> = err
+            .backtrace()
+            .iter()
+            .map(|frame| (frame.function.as_str(), frame.span.clone().start()))
+            .collect();
+        assert_eq!(frames, [("tambahin", 85), ("hitung", 116)]);
+
+        // errors outside of functions have none
+        let err = eval("1 tambah \"1\"").unwrap_err();
+        assert!(err.backtrace().is_empty());
+    }
+
+    #[test]
+    fn test_call_depth_limit() {
+        let err = eval("so about f(n) { f(n tambah 1) } f(0)").unwrap_err();
+        assert_eq!(err.message(), "maximum call depth of 1000 exceeded");
+        assert_eq!(err.kind(), ErrorKind::Limit);
+        assert_eq!(err.backtrace().len(), MAX_CALL_DEPTH);
+
+        // deep recursion that stops is fine
+        let source = "
+            so about f(n) { kalo n sama dengan 0 { 0 } kalogak { 1 tambah f(n kurang 1) } }
+            f(900)";
+        assert_eq!(eval(source).unwrap(), Value::Int(900));
+    }
+
+    #[test]
+    fn test_parse_recovery() {
+        let source = "
+            literally x itu wkwk
+            so about f() {
+                balikin ) wkwk
+                balikin 1 wkwk
+            }
+            bentuk Orang { nama umur }
+            spill(x) wkwk";
+        let (program, errors) = crate::parse_all(source);
+        let messages: Vec<&str> = errors.iter().map(|err| err.message()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Unexpected token error: wkwk",
+                "Unexpected token error: )",
+                "expected: TOK_RBRACE, found LIT_IDENT",
+            ]
+        );
+        // the statements around the broken ones are still parsed
+        assert!(matches!(program.body[0], Stmt::Error(_)));
+        let Stmt::Fn(f) = &program.body[1] else {
+            panic!("expected a function, got {:?}", program.body[1]);
+        };
+        assert!(matches!(f.body.body[..], [Stmt::Error(_), Stmt::Return(_)]));
+        assert!(matches!(program.body[2], Stmt::Error(_)));
+        assert!(matches!(program.body[3], Stmt::Expr(_)));
+    }
+
+    #[test]
+    fn test_parse_recovery_gives_up() {
+        let source = "literally x itu ) wkwk\n".repeat(30);
+        let (_, errors) = crate::parse_all(&source);
+        assert_eq!(errors.len(), 20);
+        assert_eq!(errors[19].notes(), ["stopped parsing after 20 errors"]);
+    }
+
+    #[test]
+    fn test_constants() {
+        assert_eq!(eval("seriously PI itu 3 wkwk PI").unwrap(), Value::Int(3));
+
+        let err = eval("seriously PI itu 3 wkwk { PI itu 4 wkwk }").unwrap_err();
+        assert_eq!(err.message(), "cannot assign to constant PI");
+        assert_eq!(err.span(), Span { start: 26, end: 28 });
+
+        let err = eval("seriously PI itu 3 wkwk literally PI itu 4 wkwk").unwrap_err();
+        assert_eq!(err.message(), "cannot redeclare constant PI");
+        assert_eq!(err.span(), Span { start: 34, end: 36 });
+
+        // shadowing in an inner scope declares a new binding
+        assert_eq!(
+            eval("seriously PI itu 3 wkwk { literally PI itu 4 wkwk PI }").unwrap(),
+            Value::Int(4)
+        );
+    }
+
+    #[test]
+    fn test_constants_checked_statically() {
+        // the write never runs, but the check still rejects it
+        let err =
+            crate::parse("seriously PI itu 3 wkwk so about f() { PI itu 4 wkwk }").unwrap_err();
+        assert_eq!(err.message(), "cannot assign to constant PI");
+        assert_eq!(err.span(), Span { start: 39, end: 41 });
+        assert_eq!(err.labels()[0].span, Span { start: 10, end: 12 });
+        assert_eq!(err.labels()[0].message, "declared with seriously here");
+
+        // a function sees the constants declared below it
+        let err =
+            crate::parse("so about f() { PI itu 4 wkwk } seriously PI itu 3 wkwk").unwrap_err();
+        assert_eq!(err.message(), "cannot assign to constant PI");
+        assert_eq!(err.span(), Span { start: 15, end: 17 });
+
+        let err = crate::parse("seriously f itu 3 wkwk so about f() { 1 }").unwrap_err();
+        assert_eq!(err.message(), "cannot redeclare constant f");
+        assert_eq!(err.labels()[0].span, Span { start: 10, end: 11 });
+        let err = crate::parse("bentuk T { a } seriously T itu 3 wkwk").unwrap_err();
+        assert_eq!(err.message(), "cannot redeclare constant T");
+
+        // a binding declared above the constant is still a different one
+        assert_eq!(
+            eval("literally x itu 1 wkwk x itu 2 wkwk seriously x itu x wkwk x").unwrap(),
+            Value::Int(2)
+        );
+    }
+
+    #[test]
+    fn test_while() {
+        let source = "
+            literally i itu 0 wkwk
+            literally total itu 0 wkwk
+            selama i lebih kecil 5 {
+                i itu i tambah 1 wkwk
+                total itu total tambah i wkwk
+            }
+            total";
+        assert_eq!(eval(source).unwrap(), Value::Int(15));
+    }
+
+    #[test]
+    fn test_break_continue() {
+        let source = "
+            literally i itu 0 wkwk
+            literally total itu 0 wkwk
+            selama 1 {
+                i itu i tambah 1 wkwk
+                kalo i sama dengan 3 { lanjut wkwk }
+                kalo i lebih gede 5 {
+                    { udahan wkwk }
+                }
+                total itu total tambah i wkwk
+            }
+            total";
+        // 1 + 2 + 4 + 5
+        assert_eq!(eval(source).unwrap(), Value::Int(12));
+    }
+
+    #[test]
+    fn test_break_restores_scope() {
+        let mut evaluator = Evaluator::new();
+        let source = "literally x itu 1 wkwk selama 1 { literally x itu 2 wkwk { udahan wkwk } } x";
+        let program = parse_program(&mut Cursor::new(source, lex(source)));
+        assert_eq!(evaluator.eval_program(program).unwrap(), Value::Int(1));
+    }
+
+    #[test]
+    fn test_jump_outside_loop() {
+        let err = eval("udahan wkwk").unwrap_err();
+        assert_eq!(err.message(), "udahan outside of a loop");
+        assert_eq!(err.span(), Span { start: 0, end: 6 });
+
+        let err = eval("selama 1 { so about f() { lanjut wkwk } }").unwrap_err();
+        assert_eq!(err.message(), "lanjut outside of a loop");
+    }
+
+    #[test]
+    fn test_for_range() {
+        let source = "
+            literally total itu 0 wkwk
+            tiap i dari 0 sampe 5 {
+                total itu total tambah i wkwk
+            }
+            total";
+        assert_eq!(eval(source).unwrap(), Value::Int(10));
+    }
+
+    #[test]
+    fn test_for_string() {
+        let source = r#"
+            literally s itu "" wkwk
+            tiap c dari "abc" {
+                s itu "{c}-{s}" wkwk
+            }
+            s"#;
+        assert_eq!(eval(source).unwrap(), Value::Str("c-b-a-".to_string()));
+    }
+
+    #[test]
+    fn test_for_binding_is_scoped() {
+        let err = eval("tiap i dari 0 sampe 3 { } i").unwrap_err();
+        assert_eq!(err.message(), "undefined variable: i");
+    }
+
+    #[test]
+    fn test_for_errors() {
+        let err = eval("tiap i dari 5 { }").unwrap_err();
+        assert_eq!(err.message(), "cannot iterate over int");
+        assert_eq!(err.span(), Span { start: 12, end: 13 });
+
+        let err = eval(r#"tiap i dari 0 sampe "a" { }"#).unwrap_err();
+        assert_eq!(err.span(), Span { start: 12, end: 23 });
+    }
+
+    #[test]
+    fn test_return() {
+        let source = "
+            so about cek(x) {
+                kalo x lebih kecil 0 { balikin 0 wkwk }
+                tiap i dari 0 sampe 10 {
+                    selama 1 {
+                        kalo i sama dengan x { balikin i kali 10 wkwk }
+                        udahan wkwk
+                    }
+                }
+                balikin wkwk
+            }
+            spill(cek(-1), cek(3), cek(20)) wkwk
+            cek(3)";
+        assert_eq!(eval(source).unwrap(), Value::Int(30));
+        assert_eq!(
+            eval("so about f() { balikin wkwk 1 } f()").unwrap(),
+            Value::None
+        );
+    }
+
+    #[test]
+    fn test_return_outside_function() {
+        let err = eval("balikin 1 wkwk").unwrap_err();
+        assert_eq!(err.message(), "balikin outside of a function");
+        assert_eq!(err.span(), Span { start: 0, end: 7 });
+    }
+
+    #[test]
+    fn test_env_restored_after_failed_call() {
+        let mut evaluator = Evaluator::new();
+        let source = "so about f(a) { literally b itu 1 wkwk gak_ada } f(1)";
+        let program = parse_program(&mut Cursor::new(source, lex(source)));
+        assert!(evaluator.eval_program(program).is_err());
+        assert!(evaluator.env().borrow().get("a").is_none());
+        assert!(evaluator.env().borrow().get("b").is_none());
+        assert!(evaluator.env().borrow().get("f").is_some());
+    }
+
+    #[test]
+    fn test_recursion() {
+        let source = "
+            so about fib(n) {
+                kalo n lebih kecil 2 { n } kalogak { fib(n - 1) tambah fib(n - 2) }
+            }
+            fib(15)";
+        assert_eq!(eval(source).unwrap(), Value::Int(610));
+    }
+
+    #[test]
+    fn test_mutual_recursion() {
+        let source = "
+            so about cek() {
+                // declared later in the same block
+                spill(genap(10), ganjil(7)) wkwk
+                genap(11)
+            }
+            so about genap(n) {
+                kalo n sama dengan 0 { 1 } kalogak { ganjil(n - 1) }
+            }
+            so about ganjil(n) {
+                kalo n sama dengan 0 { 0 } kalogak { genap(n - 1) }
+            }
+            cek()";
+        assert_eq!(eval(source).unwrap(), Value::Int(0));
+    }
+
+    #[test]
+    fn test_nested_recursion() {
+        let source = "
+            so about luar(n) {
+                so about hitung(i) {
+                    kalo i sama dengan 0 { 0 } kalogak { n tambah hitung(i - 1) }
+                }
+                hitung(3)
+            }
+            luar(5)";
+        assert_eq!(eval(source).unwrap(), Value::Int(15));
+    }
+
+    #[test]
+    fn test_closure_shares_scope() {
+        let source = "
+            literally x itu 1 wkwk
+            so about ambil() { x }
+            x itu 2 wkwk
+            ambil()";
+        assert_eq!(eval(source).unwrap(), Value::Int(2));
+    }
+
+    #[test]
+    fn test_debug_recursive_function() {
+        let value = eval("so about f() { f() } f").unwrap();
+        assert_eq!(
+            format!("{value:?}"),
+            r#"Function(FunctionValue { name: "f", params: [], .. })"#
+        );
+    }
+
+    #[test]
+    fn test_lambda() {
+        assert_eq!(
+            eval("literally dobel itu so about (x) { x kali 2 } wkwk dobel(21)").unwrap(),
+            Value::Int(42)
+        );
+        assert_eq!(
+            eval("(so about (a, b) { a tambah b })(1, 2)").unwrap(),
+            Value::Int(3)
+        );
+        assert_eq!(
+            eval("so about (x) { x }").unwrap().to_string(),
+            "<function lambda>"
+        );
+    }
+
+    #[test]
+    fn test_higher_order_functions() {
+        let source = "
+            so about penambah(n) {
+                so about (x) { x tambah n }
+            }
+            so about susun(f, g) {
+                so about (x) { g(f(x)) }
+            }
+            so about terapkan(f, kali_an) {
+                literally hasil itu 0 wkwk
+                tiap i dari 0 sampe kali_an {
+                    hasil itu f(hasil) wkwk
+                }
+                hasil
+            }
+            literally tambah_tiga itu susun(penambah(1), penambah(2)) wkwk
+            terapkan(tambah_tiga, 4)";
+        assert_eq!(eval(source).unwrap(), Value::Int(12));
+    }
+
+    #[test]
+    fn test_lambda_captures_loop_binding() {
+        let source = "
+            literally f itu 0 wkwk
+            tiap i dari 0 sampe 3 {
+                kalo i sama dengan 1 { f itu so about () { i } wkwk }
+            }
+            f()";
+        assert_eq!(eval(source).unwrap(), Value::Int(1));
+    }
+
+    #[test]
+    fn test_list_literal() {
+        assert_eq!(
+            eval("[1, 2 tambah 3, \"x\",]").unwrap(),
+            Value::list(vec![
+                Value::Int(1),
+                Value::Int(5),
+                Value::Str("x".to_string())
+            ])
+        );
+        assert_eq!(eval("[]").unwrap(), Value::list(vec![]));
+        assert_eq!(
+            eval(r#"[1, "a", [2, []]]"#).unwrap().to_string(),
+            r#"[1, "a", [2, []]]"#
+        );
+    }
+
+    #[test]
+    fn test_list_index() {
+        assert_eq!(eval("[1, 2, 3][0]").unwrap(), Value::Int(1));
+        assert_eq!(eval("[1, 2, 3][-1]").unwrap(), Value::Int(3));
+        assert_eq!(eval("[[1, 2], [3]][0][1]").unwrap(), Value::Int(2));
+        assert_eq!(eval(r#""halo"[-3]"#).unwrap(), Value::Str("a".to_string()));
+    }
+
+    #[test]
+    fn test_list_index_errors() {
+        let err = eval("literally xs itu [1, 2, 3] wkwk xs[1 tambah 2]").unwrap_err();
+        assert_eq!(err.message(), "index 3 out of bounds for length 3");
+        assert_eq!(err.span(), Span { start: 35, end: 45 });
+
+        let err = eval("[1][-2]").unwrap_err();
+        assert_eq!(err.message(), "index -2 out of bounds for length 1");
+
+        let err = eval(r#"[1]["a"]"#).unwrap_err();
+        assert_eq!(err.message(), "index must be int, got string");
+        assert_eq!(err.span(), Span { start: 4, end: 7 });
+    }
+
+    #[test]
+    fn test_list_slice() {
+        let list = |items: &[i64]| Value::list(items.iter().map(|i| Value::Int(*i)).collect());
+        assert_eq!(eval("[1, 2, 3, 4][1:3]").unwrap(), list(&[2, 3]));
+        assert_eq!(eval("[1, 2, 3, 4][:2]").unwrap(), list(&[1, 2]));
+        assert_eq!(eval("[1, 2, 3, 4][-2:]").unwrap(), list(&[3, 4]));
+        assert_eq!(eval("[1, 2, 3, 4][:]").unwrap(), list(&[1, 2, 3, 4]));
+        assert_eq!(eval("[1, 2, 3, 4][3:1]").unwrap(), list(&[]));
+        assert_eq!(eval("[1, 2, 3, 4][2:100]").unwrap(), list(&[3, 4]));
+        assert_eq!(
+            eval(r#""jaksel"[1:-1]"#).unwrap(),
+            Value::Str("akse".to_string())
+        );
+    }
+
+    #[test]
+    fn test_list_index_assignment() {
+        let source = "
+            literally xs itu [1, 2, 3] wkwk
+            literally ys itu xs wkwk
+            xs[0] itu 10 wkwk
+            ys[-1] itu xs[0] tambah 1 wkwk
+            xs";
+        assert_eq!(
+            eval(source).unwrap(),
+            Value::list(vec![Value::Int(10), Value::Int(2), Value::Int(11)])
+        );
+
+        let err = eval("literally xs itu [1] wkwk xs[1] itu 2 wkwk").unwrap_err();
+        assert_eq!(err.message(), "index 1 out of bounds for length 1");
+        assert_eq!(err.span(), Span { start: 29, end: 30 });
+    }
+
+    #[test]
+    fn test_list_natives_and_iteration() {
+        let source = "
+            literally xs itu [] wkwk
+            tiap i dari 0 sampe 4 { push(xs, i kali i) wkwk }
+            literally total itu 0 wkwk
+            tiap x dari xs { total itu total tambah x wkwk }
+            [len(xs), total, len(\"abc\")]";
+        assert_eq!(
+            eval(source).unwrap(),
+            Value::list(vec![Value::Int(4), Value::Int(14), Value::Int(3)])
+        );
+    }
+
+    #[test]
+    fn test_cyclic_values() {
+        let source = "
+            literally xs itu [1] wkwk
+            push(xs, xs) wkwk
+            literally ys itu [1] wkwk
+            push(ys, ys) wkwk
+            literally m itu [:] wkwk
+            m[\"diri\"] itu m wkwk
+            [xs, m, xs sama dengan ys, xs sama dengan xs, xs lebih kecil ys]";
+        assert_eq!(
+            eval(source).unwrap().to_string(),
+            r#"[[1, [...]], ["diri": [...]], true, true, false]"#
+        );
+
+        let source = "
+            bentuk Node { next }
+            literally n itu Node(kosong) wkwk
+            n.next itu n wkwk
+            n";
+        assert_eq!(eval(source).unwrap().to_string(), "Node(next: Node(...))");
+    }
+
+    #[test]
+    fn test_map_literal() {
+        let value = eval(r#"["nama": "Budi", 1: [2], "umur": 22]"#).unwrap();
+        assert_eq!(value.to_string(), r#"["nama": "Budi", 1: [2], "umur": 22]"#);
+        assert_eq!(eval("[:]").unwrap().to_string(), "[:]");
+        // later entries overwrite earlier ones but keep their position
+        assert_eq!(
+            eval(r#"["a": 1, "b": 2, "a": 3]"#).unwrap().to_string(),
+            r#"["a": 3, "b": 2]"#
+        );
+    }
+
+    #[test]
+    fn test_map_equality() {
+        assert_eq!(
+            eval(r#"["a": 1, "b": 2]"#).unwrap(),
+            eval(r#"["b": 2, "a": 1]"#).unwrap()
+        );
+        assert_ne!(
+            eval(r#"["a": 1]"#).unwrap(),
+            eval(r#"["a": 1, "b": 2]"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_map_index() {
+        let source = r#"
+            literally m itu ["host": "localhost"] wkwk
+            m["port"] itu 8080 wkwk
+            m["host"] itu "jaksel.id" wkwk
+            m[3] itu "tiga" wkwk
+            [m["host"], m["port"], m[3], len(m)]"#;
+        assert_eq!(
+            eval(source).unwrap().to_string(),
+            r#"["jaksel.id", 8080, "tiga", 3]"#
+        );
+
+        let err = eval(r#"literally m itu [:] wkwk m["nope"]"#).unwrap_err();
+        assert_eq!(err.message(), r#"key "nope" not found in map"#);
+        assert_eq!(err.span(), Span { start: 27, end: 33 });
+
+        let err = eval(r#"[[1]: 2]"#).unwrap_err();
+        assert_eq!(err.message(), "map keys must be int or string, got list");
+        assert_eq!(err.span(), Span { start: 1, end: 4 });
+    }
+
+    #[test]
+    fn test_map_natives() {
+        let source = r#"
+            literally m itu ["b": 1, "a": 2, "c": 3] wkwk
+            literally removed itu remove(m, "a") wkwk
+            m["a"] itu 4 wkwk
+            [keys(m), values(m), has(m, "b"), has(m, "z"), removed, remove(m, "z")]"#;
+        assert_eq!(
+            eval(source).unwrap().to_string(),
+            r#"[["b", "c", "a"], [1, 3, 4], true, false, 2, none]"#
+        );
+    }
+
+    #[test]
+    fn test_map_iteration_order() {
+        let source = r#"
+            literally m itu ["z": 1, "a": 2, "m": 3] wkwk
+            literally s itu "" wkwk
+            tiap k dari m { s itu "{s}{k}{m[k]}" wkwk }
+            s"#;
+        assert_eq!(eval(source).unwrap(), Value::Str("z1a2m3".to_string()));
+    }
+
+    #[test]
+    fn test_record() {
+        let source = r#"
+            bentuk Orang {
+                nama,
+                umur,
+                so about sapa(teman) {
+                    "halo {teman}, gue {gue.nama}"
+                }
+                so about ultah() {
+                    gue.umur itu gue.umur tambah 1 wkwk
+                }
+            }
+            literally budi itu Orang("Budi", 22) wkwk
+            budi.ultah() wkwk
+            [budi.nama, budi.umur, budi.sapa("Ani"), budi]"#;
+        assert_eq!(
+            eval(source).unwrap().to_string(),
+            r#"["Budi", 23, "halo Ani, gue Budi", Orang(nama: "Budi", umur: 23)]"#
+        );
+    }
+
+    #[test]
+    fn test_record_field_assignment() {
+        let source = r#"
+            bentuk Titik { x, y }
+            literally a itu Titik(1, 2) wkwk
+            literally b itu a wkwk
+            b.x itu 10 wkwk
+            [a.x, a.y, Titik]"#;
+        assert_eq!(eval(source).unwrap().to_string(), "[10, 2, <bentuk Titik>]");
+    }
+
+    #[test]
+    fn test_bound_method() {
+        let source = r#"
+            bentuk Counter {
+                n,
+                so about tambah_satu() { gue.n itu gue.n tambah 1 wkwk gue.n }
+            }
+            literally c itu Counter(0) wkwk
+            literally f itu c.tambah_satu wkwk
+            f() wkwk
+            f()"#;
+        assert_eq!(eval(source).unwrap(), Value::Int(2));
+    }
+
+    #[test]
+    fn test_record_errors() {
+        let err = eval("bentuk T { a } T(1).b").unwrap_err();
+        assert_eq!(err.message(), "T has no field b");
+        assert_eq!(err.span(), Span { start: 20, end: 21 });
+
+        let err = eval("bentuk T { a } literally t itu T(1) wkwk t.b itu 2 wkwk").unwrap_err();
+        assert_eq!(err.message(), "T has no field b");
+
+        let err = eval("1.a").unwrap_err();
+        assert_eq!(err.message(), "int has no fields");
+
+        let err = eval("bentuk T { a } T(1, 2)").unwrap_err();
+        assert_eq!(err.message(), "T expects 1 field, but got 2");
+
+        let err = eval("bentuk T { a, b, a }").unwrap_err();
+        assert_eq!(err.message(), "field a is declared twice in T");
+        assert_eq!(err.span(), Span { start: 17, end: 18 });
+        assert_eq!(err.labels()[0].span, Span { start: 11, end: 12 });
+
+        let err = eval("bentuk T { so about f() { 1 } so about f() { 2 } }").unwrap_err();
+        assert_eq!(err.message(), "method f is declared twice in T");
+        assert_eq!(err.span(), Span { start: 39, end: 40 });
+        assert_eq!(err.labels()[0].span, Span { start: 20, end: 21 });
+
+        let err = eval("bentuk A { x, so about x() { 1 } }").unwrap_err();
+        assert_eq!(err.message(), "x is both a field and a method of A");
+        assert_eq!(err.span(), Span { start: 23, end: 24 });
+        assert_eq!(err.labels()[0].span, Span { start: 11, end: 12 });
+
+        let err = crate::parse("bentuk A { x } bentuk A { y }").unwrap_err();
+        assert_eq!(err.message(), "record A is declared twice");
+        assert_eq!(err.span(), Span { start: 22, end: 23 });
+        assert_eq!(err.labels()[0].span, Span { start: 7, end: 8 });
+        // an inner block may declare its own
+        let source = "bentuk A { x } { bentuk A { y } A(1).y }";
+        assert!(crate::parse(source).is_ok());
+        assert_eq!(eval(source).unwrap(), Value::Int(1));
+    }
+
+    #[test]
+    fn test_record_hoisting() {
+        let source = "
+            so about titik() { Titik(1, 2) }
+            literally t itu titik() wkwk
+            bentuk Titik { x, y }
+            t.x tambah t.y";
+        assert_eq!(eval(source).unwrap(), Value::Int(3));
+    }
+
+    #[test]
+    fn test_match_variants() {
+        let source = r#"
+            jenis Status {
+                Loading,
+                Sukses(data),
+                Gagal(kode, pesan),
+            }
+            so about tampilin(status) {
+                cocokin status {
+                    Loading => "loading...",
+                    Sukses(data) => "dapet {data}",
+                    Gagal(404, _) => "gak ketemu",
+                    Gagal(kode, pesan) kalo kode lebih gede sama dengan 500 => "server: {pesan}",
+                    Gagal(_, pesan) => {
+                        literally teks itu "gagal: " wkwk
+                        "{teks}{pesan}"
+                    }
+                }
+            }
+            [
+                tampilin(Loading),
+                tampilin(Sukses([1, 2])),
+                tampilin(Gagal(404, "x")),
+                tampilin(Gagal(503, "down")),
+                tampilin(Gagal(400, "jelek")),
+                Gagal(1, "a"),
+                Sukses,
+            ]"#;
+        assert_eq!(
+            eval(source).unwrap().to_string(),
+            r#"["loading...", "dapet [1, 2]", "gak ketemu", "server: down", "gagal: jelek", Gagal(1, "a"), <jenis Status.Sukses>]"#
+        );
+    }
+
+    #[test]
+    fn test_match_literals_and_bindings() {
+        let source = r#"
+            so about cek(x) {
+                cocokin x {
+                    0 => "nol",
+                    -1 => "minus satu",
+                    "halo" => "sapaan",
+                    n kalo n lebih gede 100 => "gede",
+                    lain => lain,
+                }
+            }
+            [cek(0), cek(-1), cek("halo"), cek(101), cek(7)]"#;
+        assert_eq!(
+            eval(source).unwrap().to_string(),
+            r#"["nol", "minus satu", "sapaan", "gede", 7]"#
+        );
+    }
+
+    #[test]
+    fn test_match_errors() {
+        let source = "jenis T { A, B } cocokin B { A => 1 }";
+        let err = eval(source).unwrap_err();
+        assert_eq!(err.message(), "non-exhaustive cocokin: no arm matches B");
+        assert_eq!(err.span(), Span { start: 25, end: 26 });
+
+        let err = eval("cocokin 1 { Nope => 1 }").unwrap_err();
+        assert_eq!(err.message(), "Nope is not a variant");
+        assert_eq!(err.span(), Span { start: 12, end: 16 });
+
+        let err = eval("jenis T { A(x) } cocokin A(1) { A(x, y) => 1 }").unwrap_err();
+        assert_eq!(err.message(), "A has 1 field, but the pattern has 2");
+
+        let err = eval("jenis S { A, b } cocokin A { b => 2 }").unwrap_err();
+        assert_eq!(
+            err.message(),
+            "variant b must start with an uppercase letter"
+        );
+        assert_eq!(err.span(), Span { start: 13, end: 14 });
+
+        let err = eval("jenis T { A(x) } A(1, 2)").unwrap_err();
+        assert_eq!(err.message(), "A expects 1 field, but got 2");
+    }
+
+    #[test]
+    fn test_match_bindings_are_scoped() {
+        let source = r#"
+            literally x itu 1 wkwk
+            cocokin 5 { x => x } wkwk
+            x"#;
+        assert_eq!(eval(source).unwrap(), Value::Int(1));
+    }
+
+    #[test]
+    fn test_floats() {
+        let source =
+            "[1.5, 1e-3, 2.5e2, 1.0, -0.5, 1.5 tambah 1, 7 bagi 2, 7.0 bagi 2, 0.1 tambah 0.2]";
+        assert_eq!(
+            eval(source).unwrap().to_string(),
+            "[1.5, 0.001, 250.0, 1.0, -0.5, 2.5, 3, 3.5, 0.30000000000000004]"
+        );
+    }
+
+    #[test]
+    fn test_float_comparison() {
+        let source =
+            "[1 lebih kecil 1.5, 2.0 lebih gede sama dengan 2, 1 sama dengan 1.0, 0.5 gak 0.5]";
+        assert_eq!(
+            eval(source).unwrap().to_string(),
+            "[true, true, true, false]"
+        );
+    }
+
+    #[test]
+    fn test_number_conversions() {
+        let source = r#"[int(3.9), int(-3.9), int(" 42 "), float(2), float("1e3"), str(1.5)]"#;
+        assert_eq!(
+            eval(source).unwrap().to_string(),
+            r#"[3, -3, 42, 2.0, 1000.0, "1.5"]"#
+        );
+
+        let err = eval(r#"int("abc")"#).unwrap_err();
+        assert_eq!(err.message(), r#"cannot convert "abc" to int"#);
+        let err = eval("int(1e300)").unwrap_err();
+        assert_eq!(err.message(), "cannot convert 1e300 to int");
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let err = eval("1 bagi 0").unwrap_err();
+        assert_eq!(err.message(), "division by zero");
+        assert_eq!(err.span(), Span { start: 2, end: 6 });
+
+        let err = eval("9223372036854775807 tambah 1").unwrap_err();
+        assert_eq!(err.message(), "integer overflow");
+        assert_eq!(err.span(), Span { start: 20, end: 26 });
+
+        for source in [
+            "-9223372036854775807 - 2",
+            "4611686018427387904 kali 2",
+            "(-9223372036854775807 - 1) bagi -1",
+            "-(-9223372036854775807 - 1)",
+        ] {
+            assert_eq!(eval(source).unwrap_err().message(), "integer overflow");
+        }
+    }
+
+    #[test]
+    fn test_min_int_literal() {
+        assert_eq!(eval("-9223372036854775808").unwrap(), Value::Int(i64::MIN));
+        let source = "cocokin -9223372036854775808 { -9223372036854775808 => yoi, _ => nope }";
+        assert_eq!(eval(source).unwrap(), Value::Bool(true));
+        // only a literal is negated up front, `-(2 ** 63)` still overflows
+        let err = eval("-(9223372036854775807 tambah 1)").unwrap_err();
+        assert_eq!(err.message(), "integer overflow");
+    }
+
+    #[test]
+    fn test_big_ints() {
+        let eval_big = |source: &str| {
+            let program = crate::parse(source)?;
+            Evaluator::new().with_big_ints(true).eval_program(program)
+        };
+        let source = "
+            literally n itu 1 wkwk
+            tiap i dari 1 sampe 31 { n itu n kali i wkwk }
+            [n, n bagi 29 bagi 30 lebih gede 1, n bagi n, -n]";
+        assert_eq!(
+            eval_big(source).unwrap().to_string(),
+            "[265252859812191058636308480000000, true, 1, -265252859812191058636308480000000]"
+        );
+        let source = "(9223372036854775807 tambah 1) - 1";
+        assert_eq!(eval_big(source).unwrap(), Value::Int(i64::MAX));
+        let err = eval_big("(9223372036854775807 tambah 1) bagi 0").unwrap_err();
+        assert_eq!(err.message(), "division by zero");
+        assert_eq!(
+            eval_big("[99999999999999999999 tambah 1, -99999999999999999999]")
+                .unwrap()
+                .to_string(),
+            "[100000000000000000000, -99999999999999999999]"
+        );
+        let err = eval("99999999999999999999").unwrap_err();
+        assert_eq!(err.message(), "integer literal is too large");
+        assert_eq!(err.span(), Span { start: 0, end: 20 });
+        let err = eval_big("[1][9223372036854775808]").unwrap_err();
+        assert_eq!(err.message(), "index must be int, got big int");
+        let err = eval_big("tiap i dari 0 sampe 9223372036854775808 { }").unwrap_err();
+        assert_eq!(
+            err.message(),
+            "range bounds must be int, got int and big int"
+        );
+        let err = eval_big("[(9223372036854775807 tambah 1): 1]").unwrap_err();
+        assert_eq!(err.message(), "map keys must be int or string, got big int");
+    }
+
+    #[test]
+    fn test_logical_operators() {
+        let source = r#"[1 dan 2, 0 dan 2, 0 atau "x", "" || 0, [] && 1, 1 lebih kecil 2 dan 3 lebih kecil 4]"#;
+        assert_eq!(
+            eval(source).unwrap().to_string(),
+            r#"[2, 0, "x", 0, [], true]"#
+        );
+    }
+
+    #[test]
+    fn test_logical_operators_short_circuit() {
+        let source = r#"
+            literally dipanggil itu [] wkwk
+            so about catat(x) { push(dipanggil, x) wkwk x }
+            catat(0) dan catat(1) wkwk
+            catat(2) atau catat(3) wkwk
+            catat(0) atau catat(4) wkwk
+            [dipanggil, 1 atau gak_ada(), 0 dan gak_ada()]"#;
+        assert_eq!(eval(source).unwrap().to_string(), "[[0, 2, 0, 4], 1, 0]");
+    }
+
+    #[test]
+    fn test_arithmetic_operators() {
+        let source = "[7 % 3, -7 sisa 3, 2 ** 10, 2 pangkat 3 pangkat 2, -2 ** 2, 2 ** -1, 7.5 % 2, 2.0 ** 0.5 lebih gede 1.41, 1 tambah 2 kali 3, 10 kurang 2 kurang 3]";
+        assert_eq!(
+            eval(source).unwrap().to_string(),
+            "[1, -1, 1024, 512, -4, 0.5, 1.5, true, 7, 5]"
+        );
+        assert_eq!(eval("1 % 0").unwrap_err().message(), "division by zero");
+        assert_eq!(eval("2 ** 63").unwrap_err().message(), "integer overflow");
+    }
+
+    #[test]
+    fn test_bitwise_operators() {
+        let source = "[6 & 3, 6 bit atau 3, 6 ^ 3, ~5, bit bukan 0, 1 << 4, -16 >> 2, 1 geser kiri 2 tambah 1, 1 | 2 & 3]";
+        assert_eq!(
+            eval(source).unwrap().to_string(),
+            "[2, 7, 5, -6, -1, 16, -4, 8, 3]"
+        );
+        assert_eq!(
+            eval("1 << 64").unwrap_err().message(),
+            "invalid shift amount 64"
+        );
+        assert_eq!(eval("1 << 63").unwrap_err().message(), "integer overflow");
+        assert_eq!(
+            eval("1.5 & 1").unwrap_err().message(),
+            "cannot apply bit dan to float and int"
+        );
+    }
+
+    #[test]
+    fn test_string_and_list_operators() {
+        let source = r#"
+            literally a itu [1] wkwk
+            literally b itu a tambah [2] wkwk
+            push(b, 3) wkwk
+            ["ab" tambah "cd", "ab" kali 3, 2 kali "-", a, b, [0] kali 3, [[1]] kali 2]"#;
+        assert_eq!(
+            eval(source).unwrap().to_string(),
+            r#"["abcd", "ababab", "--", [1], [1, 2, 3], [0, 0, 0], [[1], [1]]]"#
+        );
+        let err = eval(r#""a" kali -1"#).unwrap_err();
+        assert_eq!(
+            err.message(),
+            "cannot repeat a negative number of times (-1)"
+        );
+        for source in [
+            r#""ab" kali 9223372036854775807"#,
+            "[1, 2] kali 9223372036854775807",
+        ] {
+            let err = eval(source).unwrap_err();
+            assert_eq!(
+                err.message(),
+                "cannot repeat 9223372036854775807 times, the result would be too large"
+            );
+            assert_eq!(err.kind(), ErrorKind::Value);
+        }
+        assert_eq!(
+            eval("[] kali 9223372036854775807").unwrap(),
+            Value::list(Vec::new())
+        );
+        let err = eval(r#""a" tambah 1"#).unwrap_err();
+        assert_eq!(err.message(), "cannot apply tambah to string and int");
+        assert_eq!(err.span(), Span { start: 4, end: 10 });
+    }
+
+    #[test]
+    fn test_equality_and_ordering() {
+        let source = r#"[
+            "a" sama dengan "a", "a" gak "b", 1 sama dengan "1", [1, [2]] sama dengan [1, [2]],
+            ["a": 1] sama dengan ["a": 1], spill sama dengan spill, 1 lebih kecil 2 sama dengan 3 lebih kecil 4,
+            "abc" lebih kecil "abd", [1, 2] lebih kecil [1, 3], [1] lebih kecil [1, 0], 2 lebih gede 1.5,
+        ]"#;
+        assert_eq!(
+            eval(source).unwrap().to_string(),
+            "[true, true, false, true, true, true, true, true, true, true, true]"
+        );
+        let source = "so about f() {} literally g itu f wkwk [f sama dengan g, f sama dengan so about () {}]";
+        assert_eq!(eval(source).unwrap().to_string(), "[true, false]");
+
+        let err = eval(r#"1 lebih kecil "2""#).unwrap_err();
+        assert_eq!(err.message(), "cannot compare int with string");
+        let err = eval(r#"[1] lebih kecil ["a"]"#).unwrap_err();
+        assert_eq!(err.message(), "cannot compare int with string");
+    }
+
+    #[test]
+    fn test_big_int_operators() {
+        let eval_big = |source: &str| {
+            let program = crate::parse(source)?;
+            Evaluator::new().with_big_ints(true).eval_program(program)
+        };
+        let source = "[2 ** 100, 2 ** 100 % 1000, 1 << 63, (1 << 63) >> 62, -(2 ** 64) >> 1, 2 ** 64 lebih gede 2 ** 63]";
+        assert_eq!(
+            eval_big(source).unwrap().to_string(),
+            "[1267650600228229401496703205376, 376, 9223372036854775808, 2, -9223372036854775808, true]"
+        );
+        // equality agrees with ordering when a big int meets a float
+        let source = "
+            literally n itu 2 pangkat 64 wkwk
+            literally x itu float(n) wkwk
+            [n sama dengan x, x sama dengan n, n gak x, n lebih kecil sama dengan x, n lebih gede sama dengan x]";
+        assert_eq!(
+            eval_big(source).unwrap().to_string(),
+            "[true, true, false, true, true]"
+        );
+        let err = eval_big("2 pangkat 3000000").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Limit);
+        assert_eq!(
+            err.message(),
+            "the result of pangkat would have about 903090 digits"
+        );
+        assert_eq!(
+            eval_big("len(str((2 pangkat 64) pangkat 1000))").unwrap(),
+            Value::Int(19266)
+        );
+        let err = eval_big("(2 ** 64) & 1").unwrap_err();
+        assert_eq!(
+            err.message(),
+            "cannot apply bit dan to ints outside of the 64 bit range"
+        );
+    }
+
+    #[test]
+    fn test_literal_keywords() {
+        let source =
+            "[yoi, nope, kosong, yoi sama dengan (1 lebih kecil 2), bukan nope, kosong atau 1, {}]";
+        assert_eq!(
+            eval(source).unwrap().to_string(),
+            "[true, false, none, true, true, 1, none]"
+        );
+        let source = r#"
+            so about cek(x) {
+                cocokin x { yoi => "ya", nope => "bukan", kosong => "kosong", _ => "lain" }
+            }
+            [cek(yoi), cek(nope), cek(kosong), cek(0)]"#;
+        assert_eq!(
+            eval(source).unwrap().to_string(),
+            r#"["ya", "bukan", "kosong", "lain"]"#
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use logos::Logos;
+use logos::{Lexer, Logos};
 
 use crate::{ast::BinaryOp, span::Span};
 
@@ -8,6 +8,7 @@ pub struct Token {
     pub span: Span,
 }
 
+#[allow(non_camel_case_types)]
 #[derive(Logos, Debug, PartialEq, Clone)]
 #[logos(skip r"[ \t\n\r]+")]
 pub enum TokenKind {
@@ -21,6 +22,26 @@ pub enum TokenKind {
     KW_LET,
     #[token("seriously")]
     KW_CONST,
+    #[token("bentuk")]
+    KW_RECORD,
+    #[token("jenis")]
+    KW_ENUM,
+    #[token("cocokin")]
+    KW_MATCH,
+    #[token("selama")]
+    KW_WHILE,
+    #[token("tiap")]
+    KW_FOR,
+    #[token("dari")]
+    KW_IN,
+    #[token("sampe")]
+    KW_TO,
+    #[token("balikin")]
+    KW_RETURN,
+    #[token("udahan")]
+    KW_BREAK,
+    #[token("lanjut")]
+    KW_CONTINUE,
 
     #[token("wkwk")]
     TOK_SEMI,
@@ -32,10 +53,21 @@ pub enum TokenKind {
     TOK_LBRACE,
     #[token("}")]
     TOK_RBRACE,
+    #[token("[")]
+    TOK_LBRACKET,
+    #[token("]")]
+    TOK_RBRACKET,
+    #[token(":")]
+    TOK_COLON,
 
     #[token(",")]
     COMMA,
+    #[token(".")]
+    TOK_DOT,
+    #[token("=>")]
+    TOK_ARROW,
     #[token("-")]
+    #[token("kurang")]
     OP_MINUS,
     #[token("tambah")]
     OP_PLUS,
@@ -43,11 +75,37 @@ pub enum TokenKind {
     OP_STAR,
     #[token("bagi")]
     OP_SLASH,
+    #[token("%")]
+    #[token("sisa")]
+    OP_PERCENT,
+    #[token("**")]
+    #[token("pangkat")]
+    OP_POW,
+    #[token("&")]
+    #[token("bit dan")]
+    OP_BIT_AND,
+    #[token("|")]
+    #[token("bit atau")]
+    OP_BIT_OR,
+    #[token("^")]
+    #[token("bit xor")]
+    OP_BIT_XOR,
+    #[token("~")]
+    #[token("bit bukan")]
+    OP_BIT_NOT,
+    #[token("<<")]
+    #[token("geser kiri")]
+    OP_SHL,
+    #[token(">>")]
+    #[token("geser kanan")]
+    OP_SHR,
     #[token("itu")]
     OP_EQ,
     #[token("||")]
+    #[token("atau")]
     OP_OR,
     #[token("&&")]
+    #[token("dan")]
     OP_AND,
     #[token("sama dengan")]
     OP_EQEQ,
@@ -64,20 +122,182 @@ pub enum TokenKind {
     #[token("bukan")]
     OP_BANG,
 
+    #[token("yoi")]
+    LIT_TRUE,
+    #[token("nope")]
+    LIT_FALSE,
+    #[token("kosong")]
+    LIT_NONE,
     #[regex(r"[0-9]+")]
     LIT_INT,
+    #[regex(r"[0-9]+\.[0-9]+([eE][+-]?[0-9]+)?|[0-9]+[eE][+-]?[0-9]+")]
+    LIT_FLOAT,
 
-    #[regex(r#""([^"\\]|\\.)*""#)]
+    #[token("\"", string)]
     LIT_STR,
+    #[regex(r##"r#*""##, raw_string)]
+    LIT_RAW_STR,
 
     #[regex(r"[a-zA-Z_][a-zA-Z_0-9]*")]
     LIT_IDENT,
 
+    #[regex(r"//[^\n]*")]
+    TOK_LINE_COMMENT,
+    #[token("/*", block_comment)]
+    TOK_BLOCK_COMMENT,
+
     TOK_ERROR,
     TOK_EOF,
 }
 
+impl TokenKind {
+    /// comments, which `lex` drops but `lex_with_trivia` keeps
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self,
+            TokenKind::TOK_LINE_COMMENT | TokenKind::TOK_BLOCK_COMMENT
+        )
+    }
+}
+
+/// consume a string literal after its opening quote, either `"..."` with
+/// backslash escapes or a triple-quoted `"""..."""` which may contain
+/// unescaped quotes. `{...}` placeholders may contain nested strings.
+fn string(lex: &mut Lexer<TokenKind>) -> bool {
+    let rest = lex.remainder();
+    let end = match rest.strip_prefix("\"\"") {
+        Some(body) => body.find("\"\"\"").map(|end| 2 + end + 3),
+        None => string_end(rest.as_bytes(), 0),
+    };
+    match end {
+        Some(end) => {
+            lex.bump(end);
+            true
+        }
+        None => {
+            // an unclosed `{` swallowed the closing quote, end the error token
+            // at the first quote so the code after the string still lexes
+            lex.bump(plain_string_end(rest.as_bytes()).unwrap_or(rest.len()));
+            false
+        }
+    }
+}
+
+/// the index right after the first unescaped quote in `bytes`, ignoring
+/// placeholders
+fn plain_string_end(bytes: &[u8]) -> Option<usize> {
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return Some(i + 1),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// the index right after the closing quote of a string whose contents start
+/// at `i`, `None` when it or one of its placeholders is unclosed
+fn string_end(bytes: &[u8], mut i: usize) -> Option<usize> {
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return Some(i + 1),
+            b'{' => {
+                let end = placeholder_end(bytes, i + 1)?;
+                // a placeholder can't run across lines, so a stray `{` doesn't
+                // pair up with a brace further down the source
+                if bytes[i..end].contains(&b'\n') {
+                    return None;
+                }
+                i = end + 1;
+            }
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// the index of the `}` closing a string placeholder whose contents start at
+/// `i`
+pub(crate) fn placeholder_end(bytes: &[u8], mut i: usize) -> Option<usize> {
+    let mut depth = 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            b'"' => {
+                i = string_end(bytes, i + 1)?;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// consume a raw string after its opening `r"` or `r#"`, which ends at a
+/// quote followed by as many `#` as it started with
+fn raw_string(lex: &mut Lexer<TokenKind>) -> bool {
+    let hashes = lex.slice().len() - 2;
+    let rest = lex.remainder();
+    let close = format!("\"{}", "#".repeat(hashes));
+    match rest.find(&close) {
+        Some(end) => {
+            lex.bump(end + close.len());
+            true
+        }
+        None => {
+            lex.bump(rest.len());
+            false
+        }
+    }
+}
+
+/// consume a (possibly nested) block comment after its opening `/*`
+fn block_comment(lex: &mut Lexer<TokenKind>) -> bool {
+    let rest = lex.remainder().as_bytes();
+    let mut depth = 1;
+    let mut i = 0;
+    while i < rest.len() {
+        match &rest[i..] {
+            [b'/', b'*', ..] => {
+                depth += 1;
+                i += 2;
+            }
+            [b'*', b'/', ..] => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    lex.bump(i);
+                    return true;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    // unterminated, the rest of the source becomes an error token
+    lex.bump(rest.len());
+    false
+}
+
+/// tokenize `src` without comments
 pub fn lex(src: &str) -> Vec<Token> {
+    lex_with_trivia(src)
+        .into_iter()
+        .filter(|token| !token.kind.is_trivia())
+        .collect()
+}
+
+/// tokenize `src` keeping comments, for tools that need them
+pub fn lex_with_trivia(src: &str) -> Vec<Token> {
     TokenKind::lexer(src)
         .spanned()
         .map(|item| match item {
@@ -92,7 +312,7 @@ pub fn lex(src: &str) -> Vec<Token> {
         })
         .chain([Token {
             kind: TokenKind::TOK_EOF,
-            span: Span::empty(),
+            span: (src.len()..src.len()).into(),
         }])
         .collect()
 }
@@ -104,6 +324,13 @@ impl From<TokenKind> for BinaryOp {
             TokenKind::OP_MINUS => BinaryOp::Subtract,
             TokenKind::OP_SLASH => BinaryOp::Divide,
             TokenKind::OP_STAR => BinaryOp::Multiply,
+            TokenKind::OP_PERCENT => BinaryOp::Modulo,
+            TokenKind::OP_POW => BinaryOp::Power,
+            TokenKind::OP_BIT_AND => BinaryOp::BitAnd,
+            TokenKind::OP_BIT_OR => BinaryOp::BitOr,
+            TokenKind::OP_BIT_XOR => BinaryOp::BitXor,
+            TokenKind::OP_SHL => BinaryOp::ShiftLeft,
+            TokenKind::OP_SHR => BinaryOp::ShiftRight,
             TokenKind::OP_OR => BinaryOp::Or,
             TokenKind::OP_AND => BinaryOp::And,
             TokenKind::OP_LT => BinaryOp::LessThan,
@@ -116,3 +343,155 @@ impl From<TokenKind> for BinaryOp {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(tokens: Vec<Token>) -> Vec<TokenKind> {
+        tokens.into_iter().map(|t| t.kind).collect()
+    }
+
+    #[test]
+    fn test_comments_skipped() {
+        assert_eq!(
+            kinds(lex("1 tambah 2 // 1 + 2\n/* a /* nested */ b */ 3")),
+            vec![
+                TokenKind::LIT_INT,
+                TokenKind::OP_PLUS,
+                TokenKind::LIT_INT,
+                TokenKind::LIT_INT,
+                TokenKind::TOK_EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_comments_as_trivia() {
+        let src = "x // line\n/* a /* b */ c */";
+        let tokens = lex_with_trivia(src);
+        assert_eq!(
+            kinds(tokens.clone()),
+            vec![
+                TokenKind::LIT_IDENT,
+                TokenKind::TOK_LINE_COMMENT,
+                TokenKind::TOK_BLOCK_COMMENT,
+                TokenKind::TOK_EOF,
+            ]
+        );
+        assert_eq!(&src[tokens[1].span.clone()], "// line");
+        assert_eq!(&src[tokens[2].span.clone()], "/* a /* b */ c */");
+    }
+
+    #[test]
+    fn test_string_literals() {
+        let src = r####"
+            "a\"b" r"c\d" r#"e"f"# """g
+            "h" """
+        "####;
+        let tokens = lex(src);
+        let lexemes: Vec<_> = tokens.iter().map(|t| &src[t.span.clone()]).collect();
+        assert_eq!(
+            kinds(tokens),
+            vec![
+                TokenKind::LIT_STR,
+                TokenKind::LIT_RAW_STR,
+                TokenKind::LIT_RAW_STR,
+                TokenKind::LIT_STR,
+                TokenKind::TOK_EOF,
+            ]
+        );
+        assert_eq!(lexemes[0], r#""a\"b""#);
+        assert_eq!(lexemes[2], r##"r#"e"f"#"##);
+        assert_eq!(lexemes[3], "\"\"\"g\n            \"h\" \"\"\"");
+    }
+
+    #[test]
+    fn test_string_placeholders() {
+        let src = r#""a {f("}", "\{", "{1}")} b" x"#;
+        let tokens = lex(src);
+        assert_eq!(tokens[0].kind, TokenKind::LIT_STR);
+        assert_eq!(
+            &src[tokens[0].span.clone()],
+            r#""a {f("}", "\{", "{1}")} b""#
+        );
+        assert_eq!(tokens[1].kind, TokenKind::LIT_IDENT);
+    }
+
+    #[test]
+    fn test_unterminated_string() {
+        let tokens = lex(r#"1 "abc\""#);
+        assert_eq!(tokens[1].kind, TokenKind::TOK_ERROR);
+        assert_eq!(tokens[1].span, Span { start: 2, end: 8 });
+    }
+
+    #[test]
+    fn test_unclosed_placeholder() {
+        let tokens = lex(r#"spill("{") wkwk"#);
+        assert_eq!(tokens[2].kind, TokenKind::TOK_ERROR);
+        assert_eq!(tokens[2].span, Span { start: 6, end: 9 });
+        assert_eq!(tokens[3].kind, TokenKind::TOK_RPAREN);
+    }
+
+    #[test]
+    fn test_unterminated_block_comment() {
+        let tokens = lex("1 /* a /* b */");
+        assert_eq!(tokens[1].kind, TokenKind::TOK_ERROR);
+        assert_eq!(tokens[1].span, Span { start: 2, end: 14 });
+    }
+
+    #[test]
+    fn test_number_literals() {
+        let src = "1 1.5 1e-3 2.5E+2 x.1 1.a";
+        let tokens = lex(src);
+        let lexemes: Vec<_> = tokens.iter().map(|t| &src[t.span.clone()]).collect();
+        assert_eq!(
+            kinds(tokens),
+            vec![
+                TokenKind::LIT_INT,
+                TokenKind::LIT_FLOAT,
+                TokenKind::LIT_FLOAT,
+                TokenKind::LIT_FLOAT,
+                TokenKind::LIT_IDENT,
+                TokenKind::TOK_DOT,
+                TokenKind::LIT_INT,
+                TokenKind::LIT_INT,
+                TokenKind::TOK_DOT,
+                TokenKind::LIT_IDENT,
+                TokenKind::TOK_EOF,
+            ]
+        );
+        assert_eq!(lexemes[2], "1e-3");
+    }
+
+    #[test]
+    fn test_operator_spellings() {
+        let src = "% sisa ** pangkat & bit dan | bit atau ^ bit xor ~ bit bukan << geser kiri >> geser kanan || && kurang bitten";
+        assert_eq!(
+            kinds(lex(src)),
+            vec![
+                TokenKind::OP_PERCENT,
+                TokenKind::OP_PERCENT,
+                TokenKind::OP_POW,
+                TokenKind::OP_POW,
+                TokenKind::OP_BIT_AND,
+                TokenKind::OP_BIT_AND,
+                TokenKind::OP_BIT_OR,
+                TokenKind::OP_BIT_OR,
+                TokenKind::OP_BIT_XOR,
+                TokenKind::OP_BIT_XOR,
+                TokenKind::OP_BIT_NOT,
+                TokenKind::OP_BIT_NOT,
+                TokenKind::OP_SHL,
+                TokenKind::OP_SHL,
+                TokenKind::OP_SHR,
+                TokenKind::OP_SHR,
+                TokenKind::OP_OR,
+                TokenKind::OP_AND,
+                TokenKind::OP_MINUS,
+                TokenKind::LIT_IDENT,
+                TokenKind::TOK_EOF,
+            ]
+        );
+    }
+}
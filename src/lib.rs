@@ -1,8 +1,11 @@
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::{ast::Program, lexer::lex, parser::parse_program};
 
 pub mod ast;
+pub mod bigint;
+pub mod check;
 pub mod cursor;
+pub mod diagnostics;
 pub mod error;
 pub mod eval;
 pub mod lexer;
@@ -12,11 +15,25 @@ pub mod span;
 #[cfg(test)]
 pub mod tests;
 
-fn parse(code: &str) -> Result<Program> {
-    let mut cursor = cursor::Cursor {
-        code,
-        position: 0,
-        tokens: lex(code),
-    };
-    parse_program(&mut cursor)
+/// lex and parse a whole source file into a `Program`, then run the static
+/// checks over it, failing with the first error found
+pub fn parse(code: &str) -> Result<Program> {
+    let (program, errors) = parse_all(code);
+    match errors.into_iter().next() {
+        Some(err) => Err(err),
+        None => Ok(program),
+    }
+}
+
+/// like `parse`, but keep going after errors and return all of them, along
+/// with the program where every statement that failed to parse is a
+/// `Stmt::Error`
+pub fn parse_all(code: &str) -> (Program, Vec<Error>) {
+    let mut cursor = cursor::Cursor::new(code, lex(code));
+    let program = parse_program(&mut cursor);
+    let mut errors = cursor.errors;
+    if let Err(err) = check::check_program(&program) {
+        errors.push(err);
+    }
+    (program, errors)
 }
@@ -1,12 +1,20 @@
 use crate::ast::*;
+use crate::bigint::BigInt;
 use crate::cursor::Cursor;
-use crate::error::{Result, error};
-use crate::lexer::TokenKind;
+use crate::error::{Error, ErrorKind, Result, error};
+use crate::lexer::{Token, TokenKind, lex, placeholder_end};
+use crate::span::Span;
 
-pub fn parse_program(c: &mut Cursor) -> Result<Program> {
+/// stop collecting syntax errors after this many, the later ones are mostly
+/// caused by the earlier ones
+const MAX_ERRORS: usize = 20;
+
+/// parse a whole program, recovering from syntax errors so that all of them
+/// end up in `c.errors`
+pub fn parse_program(c: &mut Cursor) -> Program {
     let mut body = Vec::new();
-    while !c.at(crate::lexer::TokenKind::TOK_EOF) {
-        body.push(parse_stmt(c)?)
+    while !c.at(TokenKind::TOK_EOF) {
+        body.push(parse_stmt_or_recover(c, false))
     }
 
     let trailing_semi = c.was(TokenKind::TOK_SEMI);
@@ -22,25 +30,309 @@ pub fn parse_program(c: &mut Cursor) -> Result<Program> {
     } else {
         None
     };
-    Ok(Program { body, tail })
+    Program { body, tail }
 }
 
 /// parse the statement based on the keyword of the cursor position
 fn parse_stmt(c: &mut Cursor) -> Result<Stmt> {
     match c.kind() {
-        TokenKind::KW_FN => parse_stmt_fn(c),
-        TokenKind::KW_LET => parse_stmt_let(c),
+        TokenKind::KW_FN if c.peek().kind == TokenKind::LIT_IDENT => parse_stmt_fn(c),
+        TokenKind::KW_RECORD => parse_stmt_record(c),
+        TokenKind::KW_ENUM => parse_stmt_enum(c),
+        TokenKind::KW_LET | TokenKind::KW_CONST => parse_stmt_let(c),
+        TokenKind::KW_WHILE => parse_stmt_while(c),
+        TokenKind::KW_FOR => parse_stmt_for(c),
+        TokenKind::KW_RETURN => parse_stmt_return(c),
+        TokenKind::KW_BREAK | TokenKind::KW_CONTINUE => parse_stmt_jump(c),
         _ => parse_stmt_expr(c),
     }
 }
 
+/// parse a statement, or record its syntax error, skip to the start of the
+/// next statement and put an error node in its place
+fn parse_stmt_or_recover(c: &mut Cursor, in_block: bool) -> Stmt {
+    let position = c.position;
+    let start = c.current().span.start;
+    match parse_stmt(c) {
+        Ok(stmt) => stmt,
+        Err(err) => {
+            record_error(c, err);
+            if c.position == position && !c.at(TokenKind::TOK_EOF) {
+                c.advance();
+            }
+            synchronize(c, in_block);
+            Stmt::Error(Box::new(StmtError {
+                span: span_since(c, start),
+            }))
+        }
+    }
+}
+
+/// keep a syntax error, unless it is at the same place as the previous one,
+/// which makes it a leftover of the same mistake
+fn record_error(c: &mut Cursor, err: Error) {
+    if c.errors.len() >= MAX_ERRORS
+        || c.errors
+            .last()
+            .is_some_and(|last| last.span() == err.span())
+    {
+        return;
+    }
+    c.errors.push(err);
+    if c.errors.len() == MAX_ERRORS {
+        let last = c.errors.pop().unwrap();
+        c.errors
+            .push(last.with_note(format!("stopped parsing after {MAX_ERRORS} errors")));
+        // give up on the rest of the file
+        c.position = c.tokens.len() - 1;
+    }
+}
+
+/// skip tokens up to the start of the next statement: past a `wkwk`, or
+/// before a statement keyword or the `}` closing the block the statement is
+/// in, nested blocks are skipped whole
+fn synchronize(c: &mut Cursor, in_block: bool) {
+    let mut depth = 0;
+    loop {
+        match c.kind() {
+            TokenKind::TOK_EOF => return,
+            TokenKind::TOK_LBRACE => depth += 1,
+            TokenKind::TOK_RBRACE if depth > 0 => depth -= 1,
+            TokenKind::TOK_RBRACE => {
+                // at the top level there is no block to close, so the brace
+                // belongs to the broken statement
+                if !in_block {
+                    c.advance();
+                }
+                return;
+            }
+            TokenKind::TOK_SEMI if depth == 0 => {
+                c.advance();
+                return;
+            }
+            TokenKind::KW_FN
+            | TokenKind::KW_RECORD
+            | TokenKind::KW_ENUM
+            | TokenKind::KW_LET
+            | TokenKind::KW_CONST
+            | TokenKind::KW_WHILE
+            | TokenKind::KW_FOR
+            | TokenKind::KW_RETURN
+            | TokenKind::KW_BREAK
+            | TokenKind::KW_CONTINUE
+                if depth == 0 =>
+            {
+                return;
+            }
+            _ => {}
+        }
+        c.advance();
+    }
+}
+
 /// read the function name, the params, and the body
 fn parse_stmt_fn(c: &mut Cursor) -> Result<Stmt> {
-    assert!(c.eat(TokenKind::KW_FN));
+    Ok(Stmt::Fn(Box::new(parse_fn(c)?)))
+}
+
+fn parse_fn(c: &mut Cursor) -> Result<StmtFn> {
+    c.must(TokenKind::KW_FN)?;
+    let span = c.current().span;
     let name = parse_identifier(c)?;
     let params = parse_param_list(c)?;
-    let body = parse_block(c)?;
-    Ok(Stmt::Fn(Box::new(StmtFn { name, params, body })))
+    let body = parse_fn_body(c)?;
+    Ok(StmtFn {
+        name,
+        params,
+        body,
+        span,
+    })
+}
+
+/// read the record name, then its fields and methods in any order
+fn parse_stmt_record(c: &mut Cursor) -> Result<Stmt> {
+    c.must(TokenKind::KW_RECORD)?;
+    let span = c.current().span;
+    let name = parse_identifier(c)?;
+    let mut fields = Vec::new();
+    let mut methods = Vec::new();
+    // every field and method with where it was declared, so a name used
+    // twice can point at the first one
+    let mut members: Vec<(String, Span, &str)> = Vec::new();
+    c.must(TokenKind::TOK_LBRACE)?;
+    while !c.at(TokenKind::TOK_RBRACE) {
+        let is_method = c.at(TokenKind::KW_FN);
+        let (member, member_span, kind) = if is_method {
+            let method = parse_fn(c)?;
+            c.eat(TokenKind::COMMA);
+            let member = (method.name.clone(), method.span.clone(), "method");
+            methods.push(method);
+            member
+        } else {
+            let field_span = c.current().span;
+            let field = parse_identifier(c)?;
+            fields.push(field.clone());
+            (field, field_span, "field")
+        };
+        if let Some((_, first, first_kind)) = members.iter().find(|(m, ..)| *m == member) {
+            let message = if *first_kind == kind {
+                format!("{kind} {member} is declared twice in {name}")
+            } else {
+                format!("{member} is both a field and a method of {name}")
+            };
+            return Err(error(ErrorKind::Syntax, member_span, message)
+                .with_label(first.clone(), "first declared here"));
+        }
+        members.push((member, member_span, kind));
+        if !is_method && !c.eat(TokenKind::COMMA) {
+            break;
+        }
+    }
+    c.must(TokenKind::TOK_RBRACE)?;
+    Ok(Stmt::Record(Box::new(StmtRecord {
+        name,
+        fields,
+        methods,
+        span,
+    })))
+}
+
+/// read the enum name, then its variants with their optional fields
+fn parse_stmt_enum(c: &mut Cursor) -> Result<Stmt> {
+    c.must(TokenKind::KW_ENUM)?;
+    let span = c.current().span;
+    let name = parse_identifier(c)?;
+    let mut variants = Vec::new();
+    c.must(TokenKind::TOK_LBRACE)?;
+    while !c.at(TokenKind::TOK_RBRACE) {
+        let variant_span = c.current().span;
+        let name = parse_identifier(c)?;
+        // a lowercase name in a pattern binds the value instead of matching
+        // the variant, so such a variant could never be matched
+        if !name.starts_with(|ch: char| ch.is_ascii_uppercase()) {
+            return Err(error(
+                ErrorKind::Syntax,
+                variant_span,
+                format!("variant {name} must start with an uppercase letter"),
+            ));
+        }
+        let fields = if c.at(TokenKind::TOK_LPAREN) {
+            parse_param_list(c)?
+        } else {
+            Vec::new()
+        };
+        variants.push(EnumVariant { name, fields });
+        if !c.eat(TokenKind::COMMA) {
+            break;
+        }
+    }
+    c.must(TokenKind::TOK_RBRACE)?;
+    Ok(Stmt::Enum(Box::new(StmtEnum {
+        name,
+        variants,
+        span,
+    })))
+}
+
+/// parse a function body, loops around the function don't reach into it
+fn parse_fn_body(c: &mut Cursor) -> Result<Block> {
+    let loop_depth = std::mem::replace(&mut c.loop_depth, 0);
+    c.fn_depth += 1;
+    let body = parse_block(c);
+    c.fn_depth -= 1;
+    c.loop_depth = loop_depth;
+    body
+}
+
+fn parse_stmt_while(c: &mut Cursor) -> Result<Stmt> {
+    c.must(TokenKind::KW_WHILE)?;
+    let start = c.current().span.start;
+    let cond = parse_expr(c)?;
+    let span = span_since(c, start);
+    c.loop_depth += 1;
+    let body = parse_block(c);
+    c.loop_depth -= 1;
+    Ok(Stmt::While(Box::new(StmtWhile {
+        cond,
+        body: body?,
+        span,
+    })))
+}
+
+fn parse_stmt_for(c: &mut Cursor) -> Result<Stmt> {
+    c.must(TokenKind::KW_FOR)?;
+    let name = parse_identifier(c)?;
+    c.must(TokenKind::KW_IN)?;
+    let start = c.current().span.start;
+    let iter = parse_expr(c)?;
+    let end = if c.eat(TokenKind::KW_TO) {
+        Some(parse_expr(c)?)
+    } else {
+        None
+    };
+    let span = span_since(c, start);
+    c.loop_depth += 1;
+    let body = parse_block(c);
+    c.loop_depth -= 1;
+    Ok(Stmt::For(Box::new(StmtFor {
+        name,
+        iter,
+        end,
+        body: body?,
+        span,
+    })))
+}
+
+/// parse `balikin value wkwk`, which is only allowed inside functions
+fn parse_stmt_return(c: &mut Cursor) -> Result<Stmt> {
+    let span = c.must(TokenKind::KW_RETURN)?.span;
+    if c.fn_depth == 0 {
+        return Err(error(
+            ErrorKind::Syntax,
+            span,
+            "balikin outside of a function",
+        ));
+    }
+    let value = if c.at(TokenKind::TOK_SEMI) || c.at(TokenKind::TOK_RBRACE) {
+        None
+    } else {
+        Some(parse_expr(c)?)
+    };
+    parse_stmt_end(c)?;
+    Ok(Stmt::Return(Box::new(StmtReturn { value, span })))
+}
+
+/// parse `udahan` or `lanjut`, which are only allowed inside loops
+fn parse_stmt_jump(c: &mut Cursor) -> Result<Stmt> {
+    let token = c.current();
+    c.advance();
+    if c.loop_depth == 0 {
+        let keyword = c.lexeme(token.clone());
+        return Err(error(
+            ErrorKind::Syntax,
+            token.span,
+            format!("{keyword} outside of a loop"),
+        ));
+    }
+    parse_stmt_end(c)?;
+    let span = token.span;
+    Ok(match token.kind {
+        TokenKind::KW_BREAK => Stmt::Break(Box::new(StmtBreak { span })),
+        _ => Stmt::Continue(Box::new(StmtContinue { span })),
+    })
+}
+
+/// a statement ends with `wkwk`, which may be left out before a `}` or the
+/// end of the file
+fn parse_stmt_end(c: &mut Cursor) -> Result<()> {
+    if !c.eat(TokenKind::TOK_SEMI) && !c.at(TokenKind::TOK_EOF) && !c.at(TokenKind::TOK_RBRACE) {
+        return Err(error(
+            ErrorKind::Syntax,
+            c.current().span,
+            format!("expected semicolon, got {:?}", c.kind()),
+        ));
+    }
+    Ok(())
 }
 
 /// parse the name as a token
@@ -74,11 +366,11 @@ where
 }
 
 fn parse_block(c: &mut Cursor) -> Result<Block> {
-    c.must(TokenKind::TOK_LBRACE)?;
+    let start = c.must(TokenKind::TOK_LBRACE)?.span.start;
 
     let mut body = Vec::new();
-    while !c.at(TokenKind::TOK_RBRACE) {
-        body.push(parse_stmt(c)?);
+    while !c.at(TokenKind::TOK_RBRACE) && !c.at(TokenKind::TOK_EOF) {
+        body.push(parse_stmt_or_recover(c, true));
     }
 
     let trailing_semi = c.was(TokenKind::TOK_SEMI);
@@ -97,32 +389,74 @@ fn parse_block(c: &mut Cursor) -> Result<Block> {
         None
     };
 
-    Ok(Block { body, tail })
+    Ok(Block {
+        body,
+        tail,
+        span: span_since(c, start),
+    })
 }
 
 fn parse_stmt_let(c: &mut Cursor) -> Result<Stmt> {
-    c.eat(TokenKind::KW_LET);
+    let constant = c.eat(TokenKind::KW_CONST);
+    if !constant {
+        c.must(TokenKind::KW_LET)?;
+    }
+    let span = c.current().span;
     let name = parse_identifier(c)?;
     c.must(TokenKind::OP_EQ)?;
     let value = parse_expr(c)?;
     c.must(TokenKind::TOK_SEMI)?;
-    Ok(Stmt::Let(Box::new(StmtLet { name, value })))
+    Ok(Stmt::Let(Box::new(StmtLet {
+        name,
+        value,
+        constant,
+        span,
+    })))
 }
 
 fn parse_stmt_expr(c: &mut Cursor) -> Result<Stmt> {
+    let start = c.current().span.start;
     let expr = parse_expr(c)?;
 
-    // check if the next token is semicolon
-    let semi = c.eat(TokenKind::TOK_SEMI);
-    if !semi && !c.at(TokenKind::TOK_EOF) && !c.at(TokenKind::TOK_RBRACE) {
-        return Err(error(
-            c.current().span,
-            format!("expected semicolon, got {:?}", c.kind()),
-        ));
+    if c.at(TokenKind::OP_EQ) {
+        let span = span_since(c, start);
+        c.advance();
+        return parse_stmt_assign(c, expr, span);
+    }
+
+    // expressions ending in a block, like `kalo`, don't need a semicolon
+    let block_like = matches!(expr, Expr::If(_) | Expr::Block(_) | Expr::Match(_));
+    if !block_like || c.at(TokenKind::TOK_SEMI) {
+        parse_stmt_end(c)?;
     }
     Ok(Stmt::Expr(Box::new(expr)))
 }
 
+/// the span from byte `start` to the end of the last eaten token
+fn span_since(c: &Cursor, start: u32) -> Span {
+    Span {
+        start,
+        end: c.previous().span.end,
+    }
+}
+
+/// parse the value of `target itu value wkwk`, the `itu` is already eaten
+fn parse_stmt_assign(c: &mut Cursor, target: Expr, span: Span) -> Result<Stmt> {
+    if !matches!(
+        target,
+        Expr::Identifier(_) | Expr::Index(_) | Expr::Field(_)
+    ) {
+        return Err(error(ErrorKind::Syntax, span, "invalid assignment target"));
+    }
+    let value = parse_expr(c)?;
+    c.must(TokenKind::TOK_SEMI)?;
+    Ok(Stmt::Assign(Box::new(StmtAssign {
+        target,
+        value,
+        span,
+    })))
+}
+
 fn parse_expr(c: &mut Cursor) -> Result<Expr> {
     // start with 0 binding power
     parse_expr_bp(c, 0)
@@ -130,6 +464,7 @@ fn parse_expr(c: &mut Cursor) -> Result<Expr> {
 
 // parse recursive
 fn parse_expr_bp(c: &mut Cursor, min_bp: u8) -> Result<Expr> {
+    let start = c.current().span.start;
     let mut lhs = parse_primary(c)?;
     loop {
         let op_kind = c.kind();
@@ -148,6 +483,13 @@ fn parse_expr_bp(c: &mut Cursor, min_bp: u8) -> Result<Expr> {
             | TokenKind::OP_MINUS
             | TokenKind::OP_STAR
             | TokenKind::OP_SLASH
+            | TokenKind::OP_PERCENT
+            | TokenKind::OP_POW
+            | TokenKind::OP_BIT_AND
+            | TokenKind::OP_BIT_OR
+            | TokenKind::OP_BIT_XOR
+            | TokenKind::OP_SHL
+            | TokenKind::OP_SHR
             | TokenKind::OP_LT
             | TokenKind::OP_GT
             | TokenKind::OP_NEQ
@@ -159,7 +501,13 @@ fn parse_expr_bp(c: &mut Cursor, min_bp: u8) -> Result<Expr> {
                 let op_span = c.current().span;
                 c.advance();
                 let op: BinaryOp = op_kind.clone().into();
-                let rhs = parse_expr_bp(c, bp + 1)?;
+                // `pangkat` groups to the right, `2 ** 3 ** 2` is `2 ** (3 ** 2)`
+                let rhs_bp = if matches!(op, BinaryOp::Power) {
+                    bp
+                } else {
+                    bp + 1
+                };
+                let rhs = parse_expr_bp(c, rhs_bp)?;
                 lhs = Expr::Binary(Box::new(ExprBinary {
                     lhs,
                     op,
@@ -170,7 +518,22 @@ fn parse_expr_bp(c: &mut Cursor, min_bp: u8) -> Result<Expr> {
             // if open parentheses, parse the parentheses content
             TokenKind::TOK_LPAREN => {
                 let args = parse_arg_list(c)?;
-                lhs = Expr::Call(Box::new(ExprCall { args, callee: lhs }))
+                lhs = Expr::Call(Box::new(ExprCall {
+                    args,
+                    callee: lhs,
+                    span: span_since(c, start),
+                }))
+            }
+            TokenKind::TOK_LBRACKET => lhs = parse_index(c, lhs)?,
+            TokenKind::TOK_DOT => {
+                c.advance();
+                let span = c.current().span;
+                let name = parse_identifier(c)?;
+                lhs = Expr::Field(Box::new(ExprField {
+                    target: lhs,
+                    name,
+                    span,
+                }))
             }
             _ => break,
         }
@@ -193,36 +556,94 @@ fn parse_arg_list(c: &mut Cursor) -> Result<Vec<Expr>> {
     Ok(args)
 }
 
+/// parse `[index]` or `[start:end]` after `target`
+fn parse_index(c: &mut Cursor, target: Expr) -> Result<Expr> {
+    let open = c.must(TokenKind::TOK_LBRACKET)?.span;
+    let mut start = None;
+    if !c.at(TokenKind::TOK_COLON) {
+        let index_start = c.current().span.start;
+        let index = parse_expr(c)?;
+        if !c.at(TokenKind::TOK_COLON) {
+            let span = span_since(c, index_start);
+            c.must(TokenKind::TOK_RBRACKET)?;
+            return Ok(Expr::Index(Box::new(ExprIndex {
+                target,
+                index,
+                span,
+            })));
+        }
+        start = Some(index);
+    }
+
+    c.must(TokenKind::TOK_COLON)?;
+    let end = if c.at(TokenKind::TOK_RBRACKET) {
+        None
+    } else {
+        Some(parse_expr(c)?)
+    };
+    c.must(TokenKind::TOK_RBRACKET)?;
+    Ok(Expr::Slice(Box::new(ExprSlice {
+        target,
+        start,
+        end,
+        span: span_since(c, open.start),
+    })))
+}
+
+/// unary operators bind tighter than any binary operator but `pangkat`
+const UNARY_BP: u8 = 11;
+
 fn binding_power(kind: &TokenKind) -> Option<u8> {
     Some(match kind {
         TokenKind::OP_OR => 1,
         TokenKind::OP_AND => 2,
         TokenKind::OP_EQEQ | TokenKind::OP_NEQ => 3,
-        TokenKind::OP_LT | TokenKind::OP_LE => 4,
-        TokenKind::OP_GT | TokenKind::OP_GE => 5,
-        TokenKind::OP_PLUS | TokenKind::OP_MINUS => 6,
-        TokenKind::OP_STAR | TokenKind::OP_SLASH => 6,
-        TokenKind::TOK_LPAREN => 8,
+        TokenKind::OP_LT | TokenKind::OP_LE | TokenKind::OP_GT | TokenKind::OP_GE => 4,
+        TokenKind::OP_BIT_OR => 5,
+        TokenKind::OP_BIT_XOR => 6,
+        TokenKind::OP_BIT_AND => 7,
+        TokenKind::OP_SHL | TokenKind::OP_SHR => 8,
+        TokenKind::OP_PLUS | TokenKind::OP_MINUS => 9,
+        TokenKind::OP_STAR | TokenKind::OP_SLASH | TokenKind::OP_PERCENT => 10,
+        TokenKind::OP_POW => 12,
+        TokenKind::TOK_LPAREN | TokenKind::TOK_LBRACKET | TokenKind::TOK_DOT => 13,
         _ => return None,
     })
 }
 
 fn parse_primary(c: &mut Cursor) -> Result<Expr> {
-    let next_token = c.peek();
+    let span = c.current().span;
     match c.kind() {
         TokenKind::LIT_INT => {
-            let value = c.current_lexeme().parse::<i64>().unwrap();
+            let lexeme = c.current_lexeme();
+            c.advance();
+            // whether an int past 64 bits is an error depends on the evaluator
+            match lexeme.parse::<i64>() {
+                Ok(value) => Ok(Expr::Int(Box::new(ExprInt { value, span }))),
+                Err(_) => Ok(Expr::BigInt(Box::new(ExprBigInt {
+                    value: BigInt::from_digits(lexeme).unwrap(),
+                    span,
+                }))),
+            }
+        }
+        TokenKind::LIT_TRUE | TokenKind::LIT_FALSE => {
+            let value = c.at(TokenKind::LIT_TRUE);
             c.advance();
-            Ok(Expr::Int(Box::new(ExprInt { value })))
+            Ok(Expr::Bool(Box::new(ExprBool { value, span })))
         }
-        TokenKind::LIT_STR => {
-            let value = c.current_lexeme().trim_matches('"').to_string();
+        TokenKind::LIT_NONE => {
             c.advance();
-            Ok(Expr::Str(Box::new(ExprStr { value })))
+            Ok(Expr::None(Box::new(ExprNone { span })))
         }
+        TokenKind::LIT_FLOAT => {
+            let value = c.current_lexeme().parse::<f64>().unwrap();
+            c.advance();
+            Ok(Expr::Float(Box::new(ExprFloat { value, span })))
+        }
+        TokenKind::LIT_STR | TokenKind::LIT_RAW_STR => parse_string(c),
         TokenKind::LIT_IDENT => {
             let name = parse_identifier(c)?;
-            Ok(Expr::Identifier(Box::new(ExprIdent { name })))
+            Ok(Expr::Identifier(Box::new(ExprIdent { name, span })))
         }
         TokenKind::TOK_LPAREN => {
             c.must(TokenKind::TOK_LPAREN)?;
@@ -230,48 +651,425 @@ fn parse_primary(c: &mut Cursor) -> Result<Expr> {
             c.must(TokenKind::TOK_RPAREN)?;
             Ok(expr)
         }
+        TokenKind::TOK_LBRACKET => parse_expr_list(c),
         TokenKind::TOK_LBRACE => {
             let block = parse_block(c)?;
             Ok(Expr::Block(Box::new(block)))
         }
         TokenKind::KW_IF => parse_expr_if(c),
+        TokenKind::KW_MATCH => parse_expr_match(c),
+        TokenKind::KW_FN => {
+            c.advance();
+            let params = parse_param_list(c)?;
+            let body = parse_fn_body(c)?;
+            Ok(Expr::Lambda(Box::new(ExprLambda {
+                params,
+                body,
+                span: span_since(c, span.start),
+            })))
+        }
         // parse -x
-        TokenKind::OP_MINUS | TokenKind::OP_BANG => {
-            let op = if next_token.kind == TokenKind::OP_MINUS {
-                UnaryOp::Minus
-            } else {
-                UnaryOp::Not
+        TokenKind::OP_MINUS | TokenKind::OP_BANG | TokenKind::OP_BIT_NOT => {
+            let op = match c.kind() {
+                TokenKind::OP_MINUS => UnaryOp::Minus,
+                TokenKind::OP_BANG => UnaryOp::Not,
+                _ => UnaryOp::BitNot,
             };
             c.advance();
             // unary has high binding power, bcs it's directly tied to the expression
-            let rhs = parse_expr_bp(c, 7)?;
-            Ok(Expr::Unary(Box::new(ExprUnary { rhs, op })))
+            let rhs = parse_expr_bp(c, UNARY_BP)?;
+            match rhs {
+                // negate big literals right away, so `-9223372036854775808`
+                // is an int
+                Expr::BigInt(big) if matches!(op, UnaryOp::Minus) => {
+                    let span = span_since(c, span.start);
+                    let value = big.value.neg();
+                    Ok(match value.to_i64() {
+                        Some(value) => Expr::Int(Box::new(ExprInt { value, span })),
+                        None => Expr::BigInt(Box::new(ExprBigInt { value, span })),
+                    })
+                }
+                rhs => Ok(Expr::Unary(Box::new(ExprUnary { rhs, op, span }))),
+            }
         }
-        _ => Err(error(
+        TokenKind::TOK_ERROR if c.current_lexeme().starts_with('"') => {
+            let span = c.current().span;
+            Err(match unclosed_placeholder(c.current_lexeme()) {
+                Some(i) => {
+                    let start = span.start as usize + i;
+                    error(ErrorKind::Lex, start..start + 1, "unclosed `{` in string")
+                        .with_note("write `\\{` for a literal brace")
+                }
+                None => error(ErrorKind::Lex, span, "unterminated string literal"),
+            })
+        }
+        TokenKind::TOK_ERROR if c.current_lexeme().starts_with("/*") => Err(error(
+            ErrorKind::Lex,
             c.current().span,
-            format!("Unexpected token error: {}", c.current_lexeme()),
+            "unterminated block comment",
         )),
+        _ => {
+            // the lexer makes error tokens out of characters it doesn't know
+            let kind = if c.at(TokenKind::TOK_ERROR) {
+                ErrorKind::Lex
+            } else {
+                ErrorKind::Syntax
+            };
+            Err(error(
+                kind,
+                c.current().span,
+                format!("Unexpected token error: {}", c.current_lexeme()),
+            ))
+        }
+    }
+}
+
+/// strip the delimiters of a string literal, decode its escapes and parse
+/// its `{...}` placeholders
+fn parse_string(c: &mut Cursor) -> Result<Expr> {
+    let token = c.current();
+    let lexeme = c.current_lexeme();
+    c.advance();
+
+    if token.kind == TokenKind::LIT_RAW_STR {
+        let hashes = lexeme[1..].find('"').unwrap();
+        let value = lexeme[hashes + 2..lexeme.len() - hashes - 1].to_owned();
+        return Ok(Expr::Str(Box::new(ExprStr {
+            value,
+            span: token.span,
+        })));
+    }
+
+    let (start, content) = if lexeme.len() >= 6 && lexeme.starts_with("\"\"\"") {
+        let content = &lexeme[3..lexeme.len() - 3];
+        // a newline right after the opening quotes is not part of the string
+        match content.strip_prefix('\n') {
+            Some(content) => (4, content),
+            None => (3, content),
+        }
+    } else {
+        (1, &lexeme[1..lexeme.len() - 1])
+    };
+    let offset = token.span.start as usize + start;
+
+    let bytes = content.as_bytes();
+    let mut parts = Vec::new();
+    let mut literal_start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            // the braces of `\u{...}` don't open a placeholder
+            b'\\' if content[i + 1..].starts_with("u{") => {
+                i = content[i..]
+                    .find('}')
+                    .map_or(bytes.len(), |end| i + end + 1)
+            }
+            b'\\' => i += 2,
+            b'{' => {
+                let Some(end) = placeholder_end(bytes, i + 1) else {
+                    return Err(error(
+                        ErrorKind::Lex,
+                        offset + i..offset + i + 1,
+                        "unclosed `{` in string",
+                    ));
+                };
+                let literal = unescape(&content[literal_start..i], offset + literal_start)?;
+                if !literal.is_empty() {
+                    parts.push(TemplatePart::Str(literal));
+                }
+                parts.push(TemplatePart::Expr(parse_placeholder(
+                    c,
+                    offset + i + 1..offset + end,
+                )?));
+                i = end + 1;
+                literal_start = i;
+            }
+            _ => i += 1,
+        }
+    }
+
+    let literal = unescape(&content[literal_start..], offset + literal_start)?;
+    if parts.is_empty() {
+        return Ok(Expr::Str(Box::new(ExprStr {
+            value: literal,
+            span: token.span,
+        })));
+    }
+    if !literal.is_empty() {
+        parts.push(TemplatePart::Str(literal));
+    }
+    Ok(Expr::Template(Box::new(ExprTemplate {
+        parts,
+        span: token.span,
+    })))
+}
+
+/// the offset of the first `{` in a string lexeme whose placeholder never
+/// closes
+fn unclosed_placeholder(lexeme: &str) -> Option<usize> {
+    let bytes = lexeme.as_bytes();
+    let mut i = 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if lexeme[i + 1..].starts_with("u{") => {
+                i = lexeme[i..].find('}').map_or(bytes.len(), |end| i + end + 1)
+            }
+            b'\\' => i += 2,
+            b'{' => match placeholder_end(bytes, i + 1) {
+                Some(end) => i = end + 1,
+                None => return Some(i),
+            },
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// parse the expression of a string placeholder, found at `range` of the
+/// source
+fn parse_placeholder(c: &Cursor, range: std::ops::Range<usize>) -> Result<Expr> {
+    let tokens = lex(&c.code[range.clone()])
+        .into_iter()
+        .map(|token| Token {
+            kind: token.kind,
+            span: Span {
+                start: token.span.start + range.start as u32,
+                end: token.span.end + range.start as u32,
+            },
+        })
+        .collect();
+    let mut inner = Cursor::new(c.code, tokens);
+    if inner.at(TokenKind::TOK_EOF) {
+        return Err(error(
+            ErrorKind::Syntax,
+            range.start - 1..range.end + 1,
+            "empty placeholder in string",
+        ));
+    }
+    let expr = parse_expr(&mut inner)?;
+    if !inner.at(TokenKind::TOK_EOF) {
+        return Err(error(
+            ErrorKind::Syntax,
+            inner.current().span,
+            format!(
+                "expected `}}` to close the placeholder, found {:?}",
+                inner.kind()
+            ),
+        ));
+    }
+    Ok(expr)
+}
+
+/// decode the backslash escapes of `content`, which starts at byte `offset`
+/// of the source
+fn unescape(content: &str, offset: usize) -> Result<String> {
+    let mut value = String::with_capacity(content.len());
+    let mut chars = content.char_indices();
+    while let Some((i, ch)) = chars.next() {
+        if ch != '\\' {
+            value.push(ch);
+            continue;
+        }
+
+        let escape_span = |len: usize| {
+            let start = offset + i;
+            start..start + len
+        };
+        let decoded = match chars.next() {
+            Some((_, 'n')) => '\n',
+            Some((_, 't')) => '\t',
+            Some((_, 'r')) => '\r',
+            Some((_, '0')) => '\0',
+            Some((_, '\\')) => '\\',
+            Some((_, '"')) => '"',
+            Some((_, '\'')) => '\'',
+            Some((_, '{')) => '{',
+            Some((_, '}')) => '}',
+            Some((_, 'u')) => {
+                let rest = &content[i + 2..];
+                let Some(hex) = rest.strip_prefix('{').and_then(|r| r.split_once('}')) else {
+                    return Err(error(
+                        ErrorKind::Lex,
+                        escape_span(2),
+                        "expected `{` after \\u",
+                    ));
+                };
+                let len = 2 + hex.0.len() + 2;
+                let Some(ch) = u32::from_str_radix(hex.0, 16).ok().and_then(char::from_u32) else {
+                    return Err(error(
+                        ErrorKind::Lex,
+                        escape_span(len),
+                        "invalid unicode escape",
+                    ));
+                };
+                // skip over `{XXXX}`
+                for _ in 0..hex.0.chars().count() + 2 {
+                    chars.next();
+                }
+                ch
+            }
+            Some((_, other)) => {
+                return Err(error(
+                    ErrorKind::Lex,
+                    escape_span(1 + other.len_utf8()),
+                    format!("unknown escape sequence: \\{other}"),
+                ));
+            }
+            None => {
+                return Err(error(
+                    ErrorKind::Lex,
+                    escape_span(1),
+                    "unfinished escape sequence",
+                ));
+            }
+        };
+        value.push(decoded);
+    }
+    Ok(value)
+}
+
+/// parse a list literal `[a, b]`, or a map literal `[k: v]` when the first
+/// element is followed by `:`
+fn parse_expr_list(c: &mut Cursor) -> Result<Expr> {
+    let open = c.must(TokenKind::TOK_LBRACKET)?.span;
+    if c.eat(TokenKind::TOK_COLON) {
+        c.must(TokenKind::TOK_RBRACKET)?;
+        return Ok(Expr::Map(Box::new(ExprMap {
+            entries: Vec::new(),
+            span: span_since(c, open.start),
+        })));
+    }
+
+    let mut items = Vec::new();
+    let mut entries = Vec::new();
+    while !c.at(TokenKind::TOK_RBRACKET) {
+        let start = c.current().span.start;
+        let item = parse_expr(c)?;
+        if items.is_empty() && (!entries.is_empty() || c.at(TokenKind::TOK_COLON)) {
+            let span = span_since(c, start);
+            c.must(TokenKind::TOK_COLON)?;
+            let value = parse_expr(c)?;
+            entries.push(MapEntry {
+                key: item,
+                value,
+                span,
+            });
+        } else {
+            items.push(item);
+        }
+        if !c.eat(TokenKind::COMMA) {
+            break;
+        }
+    }
+    c.must(TokenKind::TOK_RBRACKET)?;
+
+    let span = span_since(c, open.start);
+    if entries.is_empty() {
+        Ok(Expr::List(Box::new(ExprList { items, span })))
+    } else {
+        Ok(Expr::Map(Box::new(ExprMap { entries, span })))
     }
 }
 
 fn parse_expr_if(c: &mut Cursor) -> Result<Expr> {
-    c.must(TokenKind::KW_IF)?;
-    // parse the parentheses
-    let cond = parse_expr(c)?;
-    let body = parse_block(c)?;
-    let mut branches = vec![IfBranch { cond, body }];
+    let start = c.must(TokenKind::KW_IF)?.span.start;
+    let mut branches = vec![parse_if_branch(c)?];
     let mut tail = None;
 
     while c.eat(TokenKind::KW_ELSE) {
         if c.eat(TokenKind::KW_IF) {
-            let cond = parse_expr(c)?;
-            let body = parse_block(c)?;
-            branches.push(IfBranch { cond, body })
+            branches.push(parse_if_branch(c)?)
         } else {
             tail = Some(parse_block(c)?);
             break;
         }
     }
 
-    Ok(Expr::If(Box::new(ExprIf { branches, tail })))
+    Ok(Expr::If(Box::new(ExprIf {
+        branches,
+        tail,
+        span: span_since(c, start),
+    })))
+}
+
+/// parse the condition and the block of a branch, after its `kalo`
+fn parse_if_branch(c: &mut Cursor) -> Result<IfBranch> {
+    let start = c.current().span.start;
+    let cond = parse_expr(c)?;
+    let span = span_since(c, start);
+    let body = parse_block(c)?;
+    Ok(IfBranch { cond, body, span })
+}
+
+/// parse `cocokin value { arm, ... }`, the comma after an arm can be left
+/// out when its body is a block
+fn parse_expr_match(c: &mut Cursor) -> Result<Expr> {
+    c.must(TokenKind::KW_MATCH)?;
+    let start = c.current().span.start;
+    let value = parse_expr(c)?;
+    let span = span_since(c, start);
+    let mut arms = Vec::new();
+    c.must(TokenKind::TOK_LBRACE)?;
+    while !c.at(TokenKind::TOK_RBRACE) {
+        let start = c.current().span.start;
+        let pattern = parse_pattern(c)?;
+        let arm_span = span_since(c, start);
+        let guard = if c.eat(TokenKind::KW_IF) {
+            Some(parse_expr(c)?)
+        } else {
+            None
+        };
+        c.must(TokenKind::TOK_ARROW)?;
+        let body = parse_expr(c)?;
+        let block = matches!(body, Expr::Block(_));
+        arms.push(MatchArm {
+            pattern,
+            guard,
+            body,
+            span: arm_span,
+        });
+        if !c.eat(TokenKind::COMMA) && !block {
+            break;
+        }
+    }
+    c.must(TokenKind::TOK_RBRACE)?;
+    Ok(Expr::Match(Box::new(ExprMatch { value, arms, span })))
+}
+
+fn parse_pattern(c: &mut Cursor) -> Result<Pattern> {
+    match c.kind() {
+        TokenKind::LIT_INT
+        | TokenKind::LIT_FLOAT
+        | TokenKind::LIT_STR
+        | TokenKind::LIT_RAW_STR
+        | TokenKind::LIT_TRUE
+        | TokenKind::LIT_FALSE
+        | TokenKind::LIT_NONE => Ok(Pattern::Literal(parse_primary(c)?)),
+        TokenKind::OP_MINUS
+            if matches!(c.peek().kind, TokenKind::LIT_INT | TokenKind::LIT_FLOAT) =>
+        {
+            Ok(Pattern::Literal(parse_primary(c)?))
+        }
+        TokenKind::LIT_IDENT => {
+            let span = c.current().span;
+            let name = parse_identifier(c)?;
+            if name == "_" {
+                return Ok(Pattern::Wildcard);
+            }
+            let fields = if c.at(TokenKind::TOK_LPAREN) {
+                Some(parse_paren_list(c, parse_pattern)?)
+            } else {
+                None
+            };
+            if fields.is_none() && !name.starts_with(|ch: char| ch.is_ascii_uppercase()) {
+                return Ok(Pattern::Binding(name));
+            }
+            Ok(Pattern::Variant(PatternVariant { name, fields, span }))
+        }
+        _ => Err(error(
+            ErrorKind::Syntax,
+            c.current().span,
+            format!("expected a pattern, got {}", c.current_lexeme()),
+        )),
+    }
 }
@@ -0,0 +1,164 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+fn script(name: &str, code: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("jaksel-cli-{}-{name}.jks", std::process::id()));
+    std::fs::write(&path, code).unwrap();
+    path
+}
+
+fn jaksel(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_jaksel"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_run() {
+    let path = script("run", r#"spill("halo", 1 tambah 2) wkwk"#);
+    let out = jaksel(&["run", path.to_str().unwrap()]);
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "halo 3\n");
+}
+
+#[test]
+fn test_run_error() {
+    let path = script("run_error", "literally x itu wkwk");
+    let out = jaksel(&[path.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.starts_with("error[JKS0002]: Unexpected token error: wkwk\n"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("run_error.jks:1:17\n"), "{stderr}");
+    assert!(
+        stderr.ends_with("1 | literally x itu wkwk\n  |                 ^^^^\n"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_run_reports_every_syntax_error() {
+    let path = script(
+        "syntax_errors",
+        "literally x itu wkwk\nspill(1 tambah) wkwk\nliterally y itu 2 wkwk\n",
+    );
+    let out = jaksel(&[path.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    let errors = stderr
+        .lines()
+        .filter(|line| line.starts_with("error[JKS0002]: "));
+    assert_eq!(errors.count(), 2, "{stderr}");
+    assert!(stderr.contains("syntax_errors.jks:1:17\n"), "{stderr}");
+    assert!(stderr.contains("syntax_errors.jks:2:15\n"), "{stderr}");
+}
+
+#[test]
+fn test_big_ints() {
+    let path = script("big_ints", "spill(9223372036854775807 tambah 1) wkwk");
+    let out = jaksel(&["run", path.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains("error[JKS0007]: integer overflow"),
+        "{stderr}"
+    );
+
+    let out = jaksel(&["--big-ints", "run", path.to_str().unwrap()]);
+    assert!(out.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "9223372036854775808\n"
+    );
+}
+
+#[test]
+fn test_tokens() {
+    let path = script("tokens", "literally x itu 1 wkwk");
+    let out = jaksel(&["tokens", path.to_str().unwrap()]);
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(
+        stdout.starts_with("0..9 KW_LET \"literally\"\n"),
+        "{stdout}"
+    );
+    assert!(stdout.ends_with("TOK_EOF \"\"\n"), "{stdout}");
+}
+
+#[test]
+fn test_tokens_closed_pipe() {
+    let path = script("closed_pipe", &"literally x itu 1 wkwk\n".repeat(20_000));
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jaksel"))
+        .args(["tokens", path.to_str().unwrap()])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // read nothing, like `| head -0`, so the writes hit a closed pipe
+    drop(child.stdout.take());
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8_lossy(&out.stderr), "");
+}
+
+#[test]
+fn test_ast() {
+    let path = script("ast", "literally x itu 1 wkwk");
+    let out = jaksel(&["ast", path.to_str().unwrap()]);
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stdout).contains("StmtLet"));
+}
+
+#[test]
+fn test_usage() {
+    assert_eq!(jaksel(&[]).status.code(), Some(2));
+    assert_eq!(jaksel(&["run"]).status.code(), Some(2));
+    assert!(jaksel(&["help"]).status.success());
+}
+
+#[test]
+fn test_repl() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jaksel"))
+        .arg("repl")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let input = "literally x itu 1 wkwk\nx tambah 1\nkalo x sama dengan 1 {\n  x kali 5\n}\n:env\ngak_ada\n";
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.starts_with("2\n5\n"), "{stdout}");
+    assert!(stdout.contains("x = 1\n"), "{stdout}");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("undefined variable: gak_ada"), "{stderr}");
+}
+
+#[test]
+fn test_explain() {
+    let out = jaksel(&["explain", "JKS0003"]);
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(
+        stdout.starts_with("JKS0003: a name that doesn't exist\n"),
+        "{stdout}"
+    );
+
+    let out = jaksel(&["explain"]);
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8_lossy(&out.stdout).lines().count(), 11);
+
+    let out = jaksel(&["explain", "JKS9999"]);
+    assert_eq!(out.status.code(), Some(1));
+}